@@ -1,23 +1,242 @@
-use anyhow::Result;
-use solana_client::rpc_client::RpcClient;
+use std::{
+    collections::HashSet,
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+
+use solana_account::Account;
+use solana_client::{
+    nonblocking::rpc_client::RpcClient as AsyncRpcClient, rpc_client::RpcClient,
+    rpc_config::RpcSendTransactionConfig,
+};
 use solana_instruction::Instruction;
-use solana_sdk::signature::Signature;
+use solana_message::{v0, AddressLookupTableAccount, Message, VersionedMessage};
+use solana_pubkey::Pubkey;
+use solana_sdk::{commitment_config::CommitmentConfig, hash::Hash, signature::Signature};
 use solana_signer::Signer;
-use solana_transaction::{self, Transaction};
+use solana_transaction::{self, versioned::VersionedTransaction, Transaction};
+use url::Url;
+
+use crate::{
+    error::WorldError,
+    fees::{
+        with_compute_budget, writable_accounts, BudgetPlan, ComputeUnitLimit, FeeStrategy,
+        MAX_COMPUTE_UNIT_LIMIT,
+    },
+    offline::UnsignedTransaction,
+    retry::{is_already_processed, needs_new_blockhash, Attempt, RetryPolicy},
+    simulation::{simulation_config, SimulationReport},
+    validator::Validator,
+};
 
 pub const BASE_LAYER_RPC_DEVNET: &str = "https://api.devnet.solana.com";
 pub const ER_LAYER_RPC_DEVNET: &str = "https://devnet-eu.magicblock.app";
+pub const ER_LAYER_RPC_DEVNET_US: &str = "https://devnet-us.magicblock.app";
+pub const ER_LAYER_RPC_DEVNET_ASIA: &str = "https://devnet-as.magicblock.app";
 
 pub const BASE_LAYER_RPC_MAINNET: &str = "https://api.mainnet-beta.solana.com";
 pub const ER_LAYER_RPC_MAINNET: &str = "https://mainnet-beta-eu.magicblock.app";
+pub const ER_LAYER_RPC_MAINNET_US: &str = "https://mainnet-beta-us.magicblock.app";
+pub const ER_LAYER_RPC_MAINNET_ASIA: &str = "https://mainnet-beta-as.magicblock.app";
 
-#[derive(Clone, Copy)]
-#[repr(u8)]
+pub const BASE_LAYER_RPC_LOCALNET: &str = "http://127.0.0.1:8899";
+pub const ER_LAYER_RPC_LOCALNET: &str = "http://127.0.0.1:7799";
+
+/// Largest serialized transaction a node accepts (the packet data size).
+pub const MAX_TRANSACTION_SIZE: usize = 1232;
+
+/// `getMultipleAccounts` accepts at most this many addresses per request.
+pub const MAX_ACCOUNTS_PER_REQUEST: usize = 100;
+
+/// How often a rebroadcasting send checks whether its transaction landed.
+const CONFIRM_POLL_INTERVAL: Duration = Duration::from_millis(400);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum RpcType {
     Mainnet,
     Devnet,
+    /// `solana-test-validator` plus a local ephemeral validator on their default ports.
+    Localnet,
+    /// A private RPC provider or any other pair of endpoints.
+    Custom {
+        base_url: String,
+        er_url: String,
+    },
+}
+
+impl RpcType {
+    pub fn base_url(&self) -> &str {
+        match self {
+            RpcType::Mainnet => BASE_LAYER_RPC_MAINNET,
+            RpcType::Devnet => BASE_LAYER_RPC_DEVNET,
+            RpcType::Localnet => BASE_LAYER_RPC_LOCALNET,
+            RpcType::Custom { base_url, .. } => base_url,
+        }
+    }
+
+    pub fn url<'a>(&'a self, layer: RpcLayer, validator: &'a Validator) -> &'a str {
+        match layer {
+            RpcLayer::BaseLayer => self.base_url(),
+            RpcLayer::Ephemeral => validator.er_url(self),
+        }
+    }
+
+    /// Websocket endpoint for `layer`, following the Solana convention of
+    /// `ws(s)://` on the next port up when the RPC URL names a port.
+    pub fn ws_url(&self, layer: RpcLayer, validator: &Validator) -> String {
+        if let (RpcLayer::Ephemeral, Some(ws_url)) = (layer, &validator.ws_url) {
+            return ws_url.clone();
+        }
+
+        let http_url = self.url(layer, validator);
+        let Ok(mut url) = Url::parse(http_url) else {
+            return http_url.to_string();
+        };
+
+        let scheme = if url.scheme() == "https" { "wss" } else { "ws" };
+        // Only fails for non-special schemes, which `Url::parse` rejected above.
+        let _ = url.set_scheme(scheme);
+        if let Some(port) = url.port() {
+            let _ = url.set_port(Some(port + 1));
+        }
+        url.to_string()
+    }
+
+    pub fn rpc_client(&self, layer: RpcLayer, validator: &Validator) -> RpcClient {
+        self.rpc_client_with_config(layer, validator, &WorldClientConfig::default())
+    }
+
+    pub fn async_rpc_client(&self, layer: RpcLayer, validator: &Validator) -> AsyncRpcClient {
+        self.async_rpc_client_with_config(layer, validator, &WorldClientConfig::default())
+    }
+
+    pub fn rpc_client_with_config(
+        &self,
+        layer: RpcLayer,
+        validator: &Validator,
+        config: &WorldClientConfig,
+    ) -> RpcClient {
+        RpcClient::new_with_timeouts_and_commitment(
+            self.url(layer, validator).to_string(),
+            config.timeout,
+            config.commitment,
+            config.confirm_timeout,
+        )
+    }
+
+    pub fn async_rpc_client_with_config(
+        &self,
+        layer: RpcLayer,
+        validator: &Validator,
+        config: &WorldClientConfig,
+    ) -> AsyncRpcClient {
+        AsyncRpcClient::new_with_timeouts_and_commitment(
+            self.url(layer, validator).to_string(),
+            config.timeout,
+            config.commitment,
+            config.confirm_timeout,
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct WorldClientConfig {
+    /// Timeout for a single RPC request.
+    pub timeout: Duration,
+    /// How long confirmation waits for a sent transaction to first show up.
+    pub confirm_timeout: Duration,
+    pub commitment: CommitmentConfig,
+    /// How long a fetched blockhash is reused before asking the node again.
+    pub blockhash_ttl: Duration,
+    /// Simulate transactions instead of sending them. Sends return the
+    /// simulated failure, or [`WorldError::DryRun`] with the report where a
+    /// signature would be. Reports are also kept on the client until
+    /// [`WorldClient::take_simulations`] drains them.
+    pub dry_run: bool,
+    /// Priority fee added to base-layer transactions.
+    pub fee_strategy: FeeStrategy,
+    /// Compute-unit limit requested by base-layer transactions.
+    pub compute_unit_limit: ComputeUnitLimit,
+    /// Lookup tables compiled into base-layer transactions, which are then
+    /// sent as v0 transactions. Leave empty to keep legacy transactions.
+    pub lookup_tables: Vec<AddressLookupTableAccount>,
+    pub retry_policy: RetryPolicy,
+}
+
+impl Default for WorldClientConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+            confirm_timeout: Duration::from_secs(5),
+            commitment: CommitmentConfig::confirmed(),
+            blockhash_ttl: Duration::from_secs(5),
+            dry_run: false,
+            fee_strategy: FeeStrategy::default(),
+            compute_unit_limit: ComputeUnitLimit::default(),
+            lookup_tables: Vec::new(),
+            retry_policy: RetryPolicy::default(),
+        }
+    }
+}
+
+/// Remembers the latest blockhash of one layer so consecutive sends skip the
+/// `getLatestBlockhash` round trip.
+pub struct BlockhashCache {
+    ttl: Duration,
+    latest: Mutex<Option<(Hash, Instant)>>,
+    /// Signatures of the transactions signed over the newest blockhash used.
+    signed: Mutex<(Hash, HashSet<Signature>)>,
+}
+
+impl BlockhashCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            latest: Mutex::new(None),
+            signed: Mutex::new((Hash::default(), HashSet::new())),
+        }
+    }
+
+    pub fn get(&self) -> Option<Hash> {
+        match *self.latest.lock().unwrap() {
+            Some((hash, fetched_at)) if fetched_at.elapsed() < self.ttl => Some(hash),
+            _ => None,
+        }
+    }
+
+    pub fn set(&self, hash: Hash) {
+        *self.latest.lock().unwrap() = Some((hash, Instant::now()));
+    }
+
+    pub fn invalidate(&self) {
+        *self.latest.lock().unwrap() = None;
+    }
+
+    /// Invalidates the cache when `error` means the node no longer knows the
+    /// blockhash a transaction was signed over. Returns whether it did.
+    pub fn invalidate_on(&self, error: &WorldError) -> bool {
+        let expired = needs_new_blockhash(error);
+        if expired {
+            self.invalidate();
+        }
+        expired
+    }
+
+    /// Records `tx` as signed for sending. Returns `false` when an identical
+    /// transaction was already signed over the same blockhash: signing is
+    /// deterministic, so the node would take it for that one.
+    pub(crate) fn mark_signed(&self, tx: &VersionedTransaction) -> bool {
+        let blockhash = *tx.message.recent_blockhash();
+        let mut signed = self.signed.lock().unwrap();
+        if signed.0 != blockhash {
+            *signed = (blockhash, HashSet::new());
+        }
+        signed.1.insert(tx.signatures[0])
+    }
 }
-#[derive(Clone, Copy)]
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u8)]
 pub enum RpcLayer {
     BaseLayer,
@@ -25,56 +244,1164 @@ pub enum RpcLayer {
 }
 
 pub struct WorldClient {
-    pub cluster: RpcType,
-    // pub rpc: Option<RpcClient>,
+    cluster: RpcType,
+    validator: Validator,
+    pub config: WorldClientConfig,
+    base_rpc: RpcClient,
+    ephemeral_rpc: RpcClient,
+    base_blockhash: BlockhashCache,
+    ephemeral_blockhash: BlockhashCache,
+    simulations: Mutex<Vec<SimulationReport>>,
 }
 
 impl WorldClient {
     pub fn new(rpc_type: &RpcType) -> Self {
-        Self { cluster: *rpc_type }
+        Self::with_validator(rpc_type, Validator::default_for(rpc_type))
+    }
+
+    pub fn with_validator(rpc_type: &RpcType, validator: Validator) -> Self {
+        Self::with_config(rpc_type, validator, WorldClientConfig::default())
+    }
+
+    pub fn with_config(
+        rpc_type: &RpcType,
+        validator: Validator,
+        config: WorldClientConfig,
+    ) -> Self {
+        let base_rpc = rpc_type.rpc_client_with_config(RpcLayer::BaseLayer, &validator, &config);
+        let ephemeral_rpc =
+            rpc_type.rpc_client_with_config(RpcLayer::Ephemeral, &validator, &config);
+
+        Self {
+            cluster: rpc_type.clone(),
+            validator,
+            base_blockhash: BlockhashCache::new(config.blockhash_ttl),
+            ephemeral_blockhash: BlockhashCache::new(config.blockhash_ttl),
+            config,
+            base_rpc,
+            ephemeral_rpc,
+            simulations: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn cluster(&self) -> &RpcType {
+        &self.cluster
+    }
+
+    /// The validator whose ER endpoint this client talks to.
+    pub fn validator(&self) -> &Validator {
+        &self.validator
+    }
+
+    pub fn rpc(&self, layer: RpcLayer) -> &RpcClient {
+        match layer {
+            RpcLayer::BaseLayer => &self.base_rpc,
+            RpcLayer::Ephemeral => &self.ephemeral_rpc,
+        }
+    }
+
+    /// Lookup tables used on `layer`. The ER cannot resolve base-layer
+    /// tables, so its transactions always stay legacy.
+    pub fn lookup_tables(&self, layer: RpcLayer) -> &[AddressLookupTableAccount] {
+        match layer {
+            RpcLayer::BaseLayer => &self.config.lookup_tables,
+            RpcLayer::Ephemeral => &[],
+        }
+    }
+
+    /// Whether `instructions` fit in one transaction on `layer`, taking the
+    /// layer's lookup tables and the compute-budget instructions sends add
+    /// into account.
+    pub fn fits_in_transaction(
+        &self,
+        payer: &Pubkey,
+        instructions: &[Instruction],
+        layer: RpcLayer,
+    ) -> bool {
+        let instructions = self.reserve_compute_budget(instructions, layer);
+        versioned_transaction_size(payer, &instructions, self.lookup_tables(layer))
+            .is_ok_and(|size| size <= MAX_TRANSACTION_SIZE)
+    }
+
+    /// `instructions` with room for the compute-budget instructions a send on
+    /// `layer` would prepend; see [`BudgetPlan::reserve`].
+    pub(crate) fn reserve_compute_budget(
+        &self,
+        instructions: &[Instruction],
+        layer: RpcLayer,
+    ) -> Vec<Instruction> {
+        match layer {
+            RpcLayer::BaseLayer => BudgetPlan::new(
+                self.config.compute_unit_limit,
+                self.config.fee_strategy,
+                instructions,
+            )
+            .reserve(instructions),
+            RpcLayer::Ephemeral => instructions.to_vec(),
+        }
+    }
+
+    /// Fetches an account, returning `None` instead of an error when it does not exist.
+    pub fn get_account(
+        &self,
+        address: &Pubkey,
+        layer: RpcLayer,
+    ) -> Result<Option<Account>, WorldError> {
+        let account = self
+            .rpc(layer)
+            .get_account_with_commitment(address, self.config.commitment)?
+            .value;
+        Ok(account)
+    }
+
+    /// Fetches any number of accounts, in `getMultipleAccounts` sized chunks.
+    /// The result lines up with `addresses`.
+    pub fn get_multiple_accounts(
+        &self,
+        addresses: &[Pubkey],
+        layer: RpcLayer,
+    ) -> Result<Vec<Option<Account>>, WorldError> {
+        let mut accounts = Vec::with_capacity(addresses.len());
+        for chunk in addresses.chunks(MAX_ACCOUNTS_PER_REQUEST) {
+            let fetched = self
+                .rpc(layer)
+                .get_multiple_accounts_with_commitment(chunk, self.config.commitment)?
+                .value;
+            accounts.extend(fetched);
+        }
+        Ok(accounts)
+    }
+
+    pub fn blockhash_cache(&self, layer: RpcLayer) -> &BlockhashCache {
+        match layer {
+            RpcLayer::BaseLayer => &self.base_blockhash,
+            RpcLayer::Ephemeral => &self.ephemeral_blockhash,
+        }
+    }
+
+    pub fn latest_blockhash(&self, layer: RpcLayer) -> Result<Hash, WorldError> {
+        let cache = self.blockhash_cache(layer);
+        if let Some(hash) = cache.get() {
+            return Ok(hash);
+        }
+
+        let hash = self.rpc(layer).get_latest_blockhash()?;
+        cache.set(hash);
+        Ok(hash)
     }
 
     pub fn send_ixs(
-        &mut self,
+        &self,
         payer: &impl Signer,
         instructions: Vec<Instruction>,
         layer: RpcLayer,
-    ) -> Result<Signature> {
+    ) -> Result<Signature, WorldError> {
         self.send_ixs_with_payer(payer, &[payer], instructions, layer)
     }
 
     pub fn send_ixs_with_payer(
-        &mut self,
+        &self,
         payer: &impl Signer,
         signers: &[&dyn Signer],
         instructions: Vec<Instruction>,
         layer: RpcLayer,
-    ) -> Result<Signature> {
-        let rpc = match self.cluster {
-            RpcType::Devnet => match layer {
-                RpcLayer::BaseLayer => RpcClient::new(BASE_LAYER_RPC_DEVNET),
-                RpcLayer::Ephemeral => RpcClient::new(ER_LAYER_RPC_DEVNET),
-            },
-            RpcType::Mainnet => match layer {
-                RpcLayer::BaseLayer => RpcClient::new(BASE_LAYER_RPC_MAINNET),
-                RpcLayer::Ephemeral => RpcClient::new(ER_LAYER_RPC_MAINNET),
-            },
+    ) -> Result<Signature, WorldError> {
+        self.send_signed(payer, signers, &instructions, layer, |rpc, tx| {
+            self.send_and_confirm(rpc, tx)
+        })
+    }
+
+    /// Sends without preflight and returns as soon as the node accepts the
+    /// transaction. Pair with a [`SignatureTracker`](crate::tracker::SignatureTracker)
+    /// to learn whether it landed.
+    pub fn send_ixs_unconfirmed(
+        &self,
+        payer: &impl Signer,
+        signers: &[&dyn Signer],
+        instructions: Vec<Instruction>,
+        layer: RpcLayer,
+    ) -> Result<Signature, WorldError> {
+        self.send_signed(payer, signers, &instructions, layer, |rpc, tx| {
+            Ok(rpc.send_transaction_with_config(
+                tx,
+                RpcSendTransactionConfig {
+                    skip_preflight: true,
+                    ..RpcSendTransactionConfig::default()
+                },
+            )?)
+        })
+    }
+
+    /// Runs the instructions through `simulateTransaction` without sending them.
+    pub fn simulate_ixs(
+        &self,
+        payer: &impl Signer,
+        signers: &[&dyn Signer],
+        instructions: &[Instruction],
+        layer: RpcLayer,
+    ) -> Result<SimulationReport, WorldError> {
+        let tx = sign_versioned_transaction(
+            payer,
+            signers,
+            instructions,
+            Hash::default(),
+            self.lookup_tables(layer),
+        )?;
+        self.simulate_transaction(&tx, layer)
+    }
+
+    fn simulate_transaction(
+        &self,
+        tx: &VersionedTransaction,
+        layer: RpcLayer,
+    ) -> Result<SimulationReport, WorldError> {
+        let result = self
+            .rpc(layer)
+            .simulate_transaction_with_config(tx, simulation_config(self.config.commitment))?
+            .value;
+        Ok(SimulationReport::new(tx.signatures[0], result))
+    }
+
+    /// Builds `instructions` into a transaction for `payer` without signing
+    /// it, with the config's compute budget applied, for signers the SDK
+    /// cannot hold. Lookup tables are not used; the result is always legacy.
+    pub fn build_unsigned(
+        &self,
+        payer: &Pubkey,
+        instructions: Vec<Instruction>,
+        layer: RpcLayer,
+    ) -> Result<UnsignedTransaction, WorldError> {
+        let instructions = self.apply_compute_budget(payer, &instructions, layer)?;
+        let blockhash = self.latest_blockhash(layer)?;
+        Ok(UnsignedTransaction::new(
+            payer,
+            &instructions,
+            blockhash,
+            layer,
+            self.validator.clone(),
+        ))
+    }
+
+    /// Sends a transaction signed outside the SDK and waits for confirmation.
+    /// Retries resend the same transaction; it cannot be re-signed here, so
+    /// an expired blockhash is returned as an error.
+    pub fn submit_transaction(
+        &self,
+        transaction: &Transaction,
+        layer: RpcLayer,
+    ) -> Result<Signature, WorldError> {
+        verify_signatures(transaction)?;
+        let tx = VersionedTransaction::from(transaction.clone());
+
+        if self.config.dry_run {
+            let report = self.simulate_transaction(&tx, layer)?;
+            self.simulations.lock().unwrap().push(report.clone());
+            return Err(report.into_dry_run_error());
+        }
+
+        self.send_with_retry(tx, layer, None, |rpc, tx| self.send_and_confirm(rpc, tx))
+    }
+
+    /// Reports of the transactions simulated in dry-run mode, oldest first.
+    pub fn take_simulations(&self) -> Vec<SimulationReport> {
+        std::mem::take(&mut *self.simulations.lock().unwrap())
+    }
+
+    /// Prepends the compute-budget instructions the config asks for to
+    /// base-layer transactions, unless `instructions` already set them. The
+    /// ER does not charge priority fees, so its transactions are left alone.
+    fn apply_compute_budget(
+        &self,
+        payer: &Pubkey,
+        instructions: &[Instruction],
+        layer: RpcLayer,
+    ) -> Result<Vec<Instruction>, WorldError> {
+        if matches!(layer, RpcLayer::Ephemeral) {
+            return Ok(instructions.to_vec());
+        }
+
+        let plan = BudgetPlan::new(
+            self.config.compute_unit_limit,
+            self.config.fee_strategy,
+            instructions,
+        );
+
+        let mut units_consumed = None;
+        if plan.simulate {
+            let probe = probe_transaction(payer, instructions, self.lookup_tables(layer))?;
+            let report = self.simulate_transaction(&probe, layer)?;
+            report.result()?;
+            units_consumed = report.units_consumed;
+        }
+
+        let mut fees = Vec::new();
+        if plan.fee_percentile.is_some() {
+            fees = self
+                .rpc(RpcLayer::BaseLayer)
+                .get_recent_prioritization_fees(&writable_accounts(instructions))?;
+        }
+
+        Ok(plan.apply(units_consumed, &fees, instructions))
+    }
+
+    /// Signs against the cached blockhash and hands the transaction to `send`,
+    /// re-signing once with a fresh blockhash if the node no longer knows it.
+    fn send_signed(
+        &self,
+        payer: &impl Signer,
+        signers: &[&dyn Signer],
+        instructions: &[Instruction],
+        layer: RpcLayer,
+        send: impl Fn(&RpcClient, &VersionedTransaction) -> Result<Signature, WorldError>,
+    ) -> Result<Signature, WorldError> {
+        let instructions = &self.apply_compute_budget(&payer.pubkey(), instructions, layer)?;
+
+        if self.config.dry_run {
+            let report = self.simulate_ixs(payer, signers, instructions, layer)?;
+            self.simulations.lock().unwrap().push(report.clone());
+            return Err(report.into_dry_run_error());
+        }
+
+        let sign = |blockhash| {
+            sign_versioned_transaction(
+                payer,
+                signers,
+                instructions,
+                blockhash,
+                self.lookup_tables(layer),
+            )
+        };
+
+        let tx = self.sign_unique(layer, &sign)?;
+        self.send_with_retry(tx, layer, Some(&sign), send)
+    }
+
+    /// Signs with `sign` over the cached blockhash, or over a newer one when
+    /// an identical transaction was already signed over it, e.g. the same
+    /// write sent again after another. The node would drop or reject the
+    /// duplicate without applying it.
+    fn sign_unique(
+        &self,
+        layer: RpcLayer,
+        sign: &dyn Fn(Hash) -> Result<VersionedTransaction, WorldError>,
+    ) -> Result<VersionedTransaction, WorldError> {
+        let cache = self.blockhash_cache(layer);
+        let blockhash = self.latest_blockhash(layer)?;
+        let tx = sign(blockhash)?;
+        if cache.mark_signed(&tx) {
+            return Ok(tx);
+        }
+
+        let blockhash = self.rpc(layer).get_new_latest_blockhash(&blockhash)?;
+        cache.set(blockhash);
+        let tx = sign(blockhash)?;
+        cache.mark_signed(&tx);
+        Ok(tx)
+    }
+
+    /// Hands `tx` to `send` until it succeeds, the error is not retryable or
+    /// the policy runs out of attempts. Expired transactions are re-signed
+    /// with `resign` when the caller can.
+    fn send_with_retry(
+        &self,
+        mut tx: VersionedTransaction,
+        layer: RpcLayer,
+        resign: Option<&dyn Fn(Hash) -> Result<VersionedTransaction, WorldError>>,
+        send: impl Fn(&RpcClient, &VersionedTransaction) -> Result<Signature, WorldError>,
+    ) -> Result<Signature, WorldError> {
+        let rpc = self.rpc(layer);
+        let policy = &self.config.retry_policy;
+
+        let mut sent = HashSet::new();
+        let mut attempt = 1;
+        loop {
+            let signature = tx.signatures[0];
+            let resent = sent.contains(&signature);
+            let result = send(rpc, &tx);
+            record_send(&mut sent, signature, &result, self.blockhash_cache(layer));
+
+            match policy.next_attempt(attempt, result, signature, resent, resign.is_some()) {
+                Attempt::Done(result) => return result,
+                Attempt::Retry {
+                    backoff,
+                    resign: stale,
+                } => {
+                    thread::sleep(backoff);
+                    if let Some(resign) = resign.filter(|_| stale) {
+                        self.blockhash_cache(layer).invalidate();
+                        tx = self.sign_unique(layer, resign)?;
+                    }
+                }
+            }
+            attempt += 1;
+        }
+    }
+
+    fn send_and_confirm(
+        &self,
+        rpc: &RpcClient,
+        tx: &VersionedTransaction,
+    ) -> Result<Signature, WorldError> {
+        if self.config.retry_policy.rebroadcast {
+            self.send_and_rebroadcast(rpc, tx)
+        } else {
+            rpc.send_and_confirm_transaction(tx)
+                .map_err(|err| WorldError::from_confirm_error(err, tx.signatures[0]))
+        }
+    }
+
+    /// Sends `tx` and resends it every `rebroadcast_interval` until it
+    /// confirms or its blockhash expires.
+    fn send_and_rebroadcast(
+        &self,
+        rpc: &RpcClient,
+        tx: &VersionedTransaction,
+    ) -> Result<Signature, WorldError> {
+        let signature = rpc.send_transaction(tx)?;
+        let blockhash = *tx.message.recent_blockhash();
+        let mut sent_at = Instant::now();
+
+        loop {
+            thread::sleep(CONFIRM_POLL_INTERVAL);
+            let status =
+                rpc.get_signature_status_with_commitment(&signature, self.config.commitment)?;
+            if let Some(result) = status {
+                result?;
+                return Ok(signature);
+            }
+
+            if !rpc.is_blockhash_valid(&blockhash, CommitmentConfig::processed())? {
+                // It may have landed just before the blockhash expired.
+                return match rpc
+                    .get_signature_status_with_commitment(&signature, self.config.commitment)?
+                {
+                    Some(result) => result.map(|_| signature).map_err(WorldError::from),
+                    None => Err(WorldError::TransactionExpired(signature)),
+                };
+            }
+
+            if sent_at.elapsed() >= self.config.retry_policy.rebroadcast_interval {
+                // A failed resend is retried on the next interval.
+                let _ = rpc.send_transaction_with_config(tx, rebroadcast_config());
+                sent_at = Instant::now();
+            }
+        }
+    }
+}
+
+/// Non-blocking counterpart of [`WorldClient`] for use inside a tokio runtime.
+pub struct AsyncWorldClient {
+    cluster: RpcType,
+    validator: Validator,
+    pub config: WorldClientConfig,
+    base_rpc: AsyncRpcClient,
+    ephemeral_rpc: AsyncRpcClient,
+    base_blockhash: BlockhashCache,
+    ephemeral_blockhash: BlockhashCache,
+    simulations: Mutex<Vec<SimulationReport>>,
+}
+
+impl AsyncWorldClient {
+    pub fn new(rpc_type: &RpcType) -> Self {
+        Self::with_validator(rpc_type, Validator::default_for(rpc_type))
+    }
+
+    pub fn with_validator(rpc_type: &RpcType, validator: Validator) -> Self {
+        Self::with_config(rpc_type, validator, WorldClientConfig::default())
+    }
+
+    pub fn with_config(
+        rpc_type: &RpcType,
+        validator: Validator,
+        config: WorldClientConfig,
+    ) -> Self {
+        let base_rpc =
+            rpc_type.async_rpc_client_with_config(RpcLayer::BaseLayer, &validator, &config);
+        let ephemeral_rpc =
+            rpc_type.async_rpc_client_with_config(RpcLayer::Ephemeral, &validator, &config);
+
+        Self {
+            cluster: rpc_type.clone(),
+            validator,
+            base_blockhash: BlockhashCache::new(config.blockhash_ttl),
+            ephemeral_blockhash: BlockhashCache::new(config.blockhash_ttl),
+            config,
+            base_rpc,
+            ephemeral_rpc,
+            simulations: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn cluster(&self) -> &RpcType {
+        &self.cluster
+    }
+
+    /// The validator whose ER endpoint this client talks to.
+    pub fn validator(&self) -> &Validator {
+        &self.validator
+    }
+
+    pub fn rpc(&self, layer: RpcLayer) -> &AsyncRpcClient {
+        match layer {
+            RpcLayer::BaseLayer => &self.base_rpc,
+            RpcLayer::Ephemeral => &self.ephemeral_rpc,
+        }
+    }
+
+    pub fn lookup_tables(&self, layer: RpcLayer) -> &[AddressLookupTableAccount] {
+        match layer {
+            RpcLayer::BaseLayer => &self.config.lookup_tables,
+            RpcLayer::Ephemeral => &[],
+        }
+    }
+
+    /// Fetches an account, returning `None` instead of an error when it does not exist.
+    pub async fn get_account(
+        &self,
+        address: &Pubkey,
+        layer: RpcLayer,
+    ) -> Result<Option<Account>, WorldError> {
+        let account = self
+            .rpc(layer)
+            .get_account_with_commitment(address, self.config.commitment)
+            .await?
+            .value;
+        Ok(account)
+    }
+
+    pub fn blockhash_cache(&self, layer: RpcLayer) -> &BlockhashCache {
+        match layer {
+            RpcLayer::BaseLayer => &self.base_blockhash,
+            RpcLayer::Ephemeral => &self.ephemeral_blockhash,
+        }
+    }
+
+    pub async fn latest_blockhash(&self, layer: RpcLayer) -> Result<Hash, WorldError> {
+        let cache = self.blockhash_cache(layer);
+        if let Some(hash) = cache.get() {
+            return Ok(hash);
+        }
+
+        let hash = self.rpc(layer).get_latest_blockhash().await?;
+        cache.set(hash);
+        Ok(hash)
+    }
+
+    pub async fn send_ixs(
+        &self,
+        payer: &(impl Signer + Sync),
+        instructions: Vec<Instruction>,
+        layer: RpcLayer,
+    ) -> Result<Signature, WorldError> {
+        self.send_ixs_with_payer(payer, &[payer], instructions, layer)
+            .await
+    }
+
+    pub async fn send_ixs_with_payer(
+        &self,
+        payer: &(impl Signer + Sync),
+        signers: &[&(dyn Signer + Sync)],
+        instructions: Vec<Instruction>,
+        layer: RpcLayer,
+    ) -> Result<Signature, WorldError> {
+        let instructions = self
+            .apply_compute_budget(&payer.pubkey(), &instructions, layer)
+            .await?;
+
+        if self.config.dry_run {
+            let report = self
+                .simulate_ixs(payer, signers, &instructions, layer)
+                .await?;
+            self.simulations.lock().unwrap().push(report.clone());
+            return Err(report.into_dry_run_error());
+        }
+
+        let sign = |blockhash| {
+            let signers: Vec<&dyn Signer> = signers.iter().map(|s| *s as &dyn Signer).collect();
+            sign_versioned_transaction(
+                payer,
+                &signers,
+                &instructions,
+                blockhash,
+                self.lookup_tables(layer),
+            )
         };
 
-        let blockhash = rpc.get_latest_blockhash()?;
+        let tx = self.sign_unique(layer, &sign).await?;
+        self.send_with_retry(tx, layer, Some(&sign)).await
+    }
 
-        let mut all_signers: Vec<&dyn Signer> = signers.to_vec();
-        if !all_signers.iter().any(|s| s.pubkey() == payer.pubkey()) {
-            all_signers.push(payer);
+    /// See [`WorldClient::sign_unique`].
+    async fn sign_unique(
+        &self,
+        layer: RpcLayer,
+        sign: &(dyn Fn(Hash) -> Result<VersionedTransaction, WorldError> + Sync),
+    ) -> Result<VersionedTransaction, WorldError> {
+        let cache = self.blockhash_cache(layer);
+        let blockhash = self.latest_blockhash(layer).await?;
+        let tx = sign(blockhash)?;
+        if cache.mark_signed(&tx) {
+            return Ok(tx);
         }
 
-        let tx = Transaction::new_signed_with_payer(
+        let blockhash = self.rpc(layer).get_new_latest_blockhash(&blockhash).await?;
+        cache.set(blockhash);
+        let tx = sign(blockhash)?;
+        cache.mark_signed(&tx);
+        Ok(tx)
+    }
+
+    /// Builds `instructions` into a transaction for `payer` without signing
+    /// it, like [`WorldClient::build_unsigned`].
+    pub async fn build_unsigned(
+        &self,
+        payer: &Pubkey,
+        instructions: Vec<Instruction>,
+        layer: RpcLayer,
+    ) -> Result<UnsignedTransaction, WorldError> {
+        let instructions = self
+            .apply_compute_budget(payer, &instructions, layer)
+            .await?;
+        let blockhash = self.latest_blockhash(layer).await?;
+        Ok(UnsignedTransaction::new(
+            payer,
             &instructions,
-            Some(&payer.pubkey()),
-            &all_signers,
             blockhash,
+            layer,
+            self.validator.clone(),
+        ))
+    }
+
+    /// Sends a transaction signed outside the SDK and waits for confirmation,
+    /// like [`WorldClient::submit_transaction`].
+    pub async fn submit_transaction(
+        &self,
+        transaction: &Transaction,
+        layer: RpcLayer,
+    ) -> Result<Signature, WorldError> {
+        verify_signatures(transaction)?;
+        let tx = VersionedTransaction::from(transaction.clone());
+
+        if self.config.dry_run {
+            let report = self.simulate_transaction(&tx, layer).await?;
+            self.simulations.lock().unwrap().push(report.clone());
+            return Err(report.into_dry_run_error());
+        }
+
+        self.send_with_retry(tx, layer, None).await
+    }
+
+    /// Sends `tx` until it confirms, the error is not retryable or the policy
+    /// runs out of attempts, re-signing expired transactions with `resign`
+    /// when the caller can.
+    async fn send_with_retry(
+        &self,
+        mut tx: VersionedTransaction,
+        layer: RpcLayer,
+        resign: Option<&(dyn Fn(Hash) -> Result<VersionedTransaction, WorldError> + Sync)>,
+    ) -> Result<Signature, WorldError> {
+        let rpc = self.rpc(layer);
+        let policy = &self.config.retry_policy;
+
+        let mut sent = HashSet::new();
+        let mut attempt = 1;
+        loop {
+            let signature = tx.signatures[0];
+            let resent = sent.contains(&signature);
+            let result = self.send_and_confirm(rpc, &tx).await;
+            record_send(&mut sent, signature, &result, self.blockhash_cache(layer));
+
+            match policy.next_attempt(attempt, result, signature, resent, resign.is_some()) {
+                Attempt::Done(result) => return result,
+                Attempt::Retry {
+                    backoff,
+                    resign: stale,
+                } => {
+                    tokio::time::sleep(backoff).await;
+                    if let Some(resign) = resign.filter(|_| stale) {
+                        self.blockhash_cache(layer).invalidate();
+                        tx = self.sign_unique(layer, resign).await?;
+                    }
+                }
+            }
+            attempt += 1;
+        }
+    }
+
+    async fn send_and_confirm(
+        &self,
+        rpc: &AsyncRpcClient,
+        tx: &VersionedTransaction,
+    ) -> Result<Signature, WorldError> {
+        if self.config.retry_policy.rebroadcast {
+            self.send_and_rebroadcast(rpc, tx).await
+        } else {
+            rpc.send_and_confirm_transaction(tx)
+                .await
+                .map_err(|err| WorldError::from_confirm_error(err, tx.signatures[0]))
+        }
+    }
+
+    async fn send_and_rebroadcast(
+        &self,
+        rpc: &AsyncRpcClient,
+        tx: &VersionedTransaction,
+    ) -> Result<Signature, WorldError> {
+        let signature = rpc.send_transaction(tx).await?;
+        let blockhash = *tx.message.recent_blockhash();
+        let mut sent_at = Instant::now();
+
+        loop {
+            tokio::time::sleep(CONFIRM_POLL_INTERVAL).await;
+            let status = rpc
+                .get_signature_status_with_commitment(&signature, self.config.commitment)
+                .await?;
+            if let Some(result) = status {
+                result?;
+                return Ok(signature);
+            }
+
+            if !rpc
+                .is_blockhash_valid(&blockhash, CommitmentConfig::processed())
+                .await?
+            {
+                return match rpc
+                    .get_signature_status_with_commitment(&signature, self.config.commitment)
+                    .await?
+                {
+                    Some(result) => result.map(|_| signature).map_err(WorldError::from),
+                    None => Err(WorldError::TransactionExpired(signature)),
+                };
+            }
+
+            if sent_at.elapsed() >= self.config.retry_policy.rebroadcast_interval {
+                let _ = rpc
+                    .send_transaction_with_config(tx, rebroadcast_config())
+                    .await;
+                sent_at = Instant::now();
+            }
+        }
+    }
+
+    async fn apply_compute_budget(
+        &self,
+        payer: &Pubkey,
+        instructions: &[Instruction],
+        layer: RpcLayer,
+    ) -> Result<Vec<Instruction>, WorldError> {
+        if matches!(layer, RpcLayer::Ephemeral) {
+            return Ok(instructions.to_vec());
+        }
+
+        let plan = BudgetPlan::new(
+            self.config.compute_unit_limit,
+            self.config.fee_strategy,
+            instructions,
         );
-        let tx = rpc.send_and_confirm_transaction(&tx)?;
-        Ok(tx)
+
+        let mut units_consumed = None;
+        if plan.simulate {
+            let probe = probe_transaction(payer, instructions, self.lookup_tables(layer))?;
+            let report = self.simulate_transaction(&probe, layer).await?;
+            report.result()?;
+            units_consumed = report.units_consumed;
+        }
+
+        let mut fees = Vec::new();
+        if plan.fee_percentile.is_some() {
+            fees = self
+                .rpc(RpcLayer::BaseLayer)
+                .get_recent_prioritization_fees(&writable_accounts(instructions))
+                .await?;
+        }
+
+        Ok(plan.apply(units_consumed, &fees, instructions))
+    }
+
+    pub async fn simulate_ixs(
+        &self,
+        payer: &(impl Signer + Sync),
+        signers: &[&(dyn Signer + Sync)],
+        instructions: &[Instruction],
+        layer: RpcLayer,
+    ) -> Result<SimulationReport, WorldError> {
+        let tx = {
+            let signers: Vec<&dyn Signer> = signers.iter().map(|s| *s as &dyn Signer).collect();
+            sign_versioned_transaction(
+                payer,
+                &signers,
+                instructions,
+                Hash::default(),
+                self.lookup_tables(layer),
+            )?
+        };
+        self.simulate_transaction(&tx, layer).await
+    }
+
+    async fn simulate_transaction(
+        &self,
+        tx: &VersionedTransaction,
+        layer: RpcLayer,
+    ) -> Result<SimulationReport, WorldError> {
+        let result = self
+            .rpc(layer)
+            .simulate_transaction_with_config(tx, simulation_config(self.config.commitment))
+            .await?
+            .value;
+        Ok(SimulationReport::new(tx.signatures[0], result))
+    }
+
+    pub fn take_simulations(&self) -> Vec<SimulationReport> {
+        std::mem::take(&mut *self.simulations.lock().unwrap())
+    }
+}
+
+/// Notes the outcome of sending `signature` in a retry loop. A send the node
+/// turned away as `AlreadyProcessed` did not go out, so it is not counted as
+/// sent by this loop.
+fn record_send(
+    sent: &mut HashSet<Signature>,
+    signature: Signature,
+    result: &Result<Signature, WorldError>,
+    cache: &BlockhashCache,
+) {
+    match result {
+        Err(err) if is_already_processed(err) => {}
+        Err(err) => {
+            cache.invalidate_on(err);
+            sent.insert(signature);
+        }
+        Ok(_) => {
+            sent.insert(signature);
+        }
+    }
+}
+
+/// Resends skip preflight: the first send already ran it, and a duplicate
+/// would only fail it as already processed.
+fn rebroadcast_config() -> RpcSendTransactionConfig {
+    RpcSendTransactionConfig {
+        skip_preflight: true,
+        ..RpcSendTransactionConfig::default()
+    }
+}
+
+/// Serialized size of a legacy transaction carrying `instructions`, signatures included.
+pub fn transaction_size(payer: &Pubkey, instructions: &[Instruction]) -> usize {
+    let tx = Transaction::new_with_payer(instructions, Some(payer));
+    // Signature count is a compact-u16; it fits one byte below 128 signers.
+    1 + tx.signatures.len() * 64 + tx.message_data().len()
+}
+
+pub fn fits_in_transaction(payer: &Pubkey, instructions: &[Instruction]) -> bool {
+    transaction_size(payer, instructions) <= MAX_TRANSACTION_SIZE
+}
+
+/// Like [`transaction_size`], for a v0 transaction resolving accounts through
+/// `lookup_tables`. Without tables this is the legacy size.
+pub fn versioned_transaction_size(
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    lookup_tables: &[AddressLookupTableAccount],
+) -> Result<usize, WorldError> {
+    if lookup_tables.is_empty() {
+        return Ok(transaction_size(payer, instructions));
+    }
+
+    let message = compile_v0_message(payer, instructions, lookup_tables, Hash::default())?;
+    let signatures = message.header.num_required_signatures as usize;
+    Ok(1 + signatures * 64 + VersionedMessage::V0(message).serialize().len())
+}
+
+/// Splits `instructions` into as few transactions as the size limit allows,
+/// keeping their order. Fails if a single instruction does not fit on its own.
+pub fn pack_instructions(
+    payer: &Pubkey,
+    instructions: Vec<Instruction>,
+) -> Result<Vec<Vec<Instruction>>, WorldError> {
+    let mut batches = Vec::new();
+    let mut batch: Vec<Instruction> = Vec::new();
+
+    for ix in instructions {
+        batch.push(ix);
+        if fits_in_transaction(payer, &batch) {
+            continue;
+        }
+
+        let ix = batch
+            .pop()
+            .expect("batch holds the instruction just pushed");
+        if batch.is_empty() {
+            return Err(WorldError::TransactionTooLarge {
+                size: transaction_size(payer, std::slice::from_ref(&ix)),
+                max: MAX_TRANSACTION_SIZE,
+            });
+        }
+        batches.push(std::mem::replace(&mut batch, vec![ix]));
+    }
+
+    if !batch.is_empty() {
+        batches.push(batch);
+    }
+    Ok(batches)
+}
+
+fn sign_transaction(
+    payer: &impl Signer,
+    signers: &[&dyn Signer],
+    instructions: &[Instruction],
+    blockhash: Hash,
+) -> Transaction {
+    let mut all_signers: Vec<&dyn Signer> = signers.to_vec();
+    if !all_signers.iter().any(|s| s.pubkey() == payer.pubkey()) {
+        all_signers.push(payer);
+    }
+
+    Transaction::new_signed_with_payer(instructions, Some(&payer.pubkey()), &all_signers, blockhash)
+}
+
+/// Signs a v0 transaction when lookup tables are given, a legacy one otherwise.
+fn sign_versioned_transaction(
+    payer: &impl Signer,
+    signers: &[&dyn Signer],
+    instructions: &[Instruction],
+    blockhash: Hash,
+    lookup_tables: &[AddressLookupTableAccount],
+) -> Result<VersionedTransaction, WorldError> {
+    if lookup_tables.is_empty() {
+        return Ok(sign_transaction(payer, signers, instructions, blockhash).into());
+    }
+
+    let message = compile_v0_message(&payer.pubkey(), instructions, lookup_tables, blockhash)?;
+    let mut all_signers: Vec<&dyn Signer> = vec![payer];
+    for signer in signers {
+        if !all_signers.iter().any(|s| s.pubkey() == signer.pubkey()) {
+            all_signers.push(*signer);
+        }
+    }
+
+    VersionedTransaction::try_new(VersionedMessage::V0(message), &all_signers)
+        .map_err(|err| WorldError::TransactionBuildError(err.to_string()))
+}
+
+/// An unsigned transaction asking for the maximum compute budget, simulated
+/// to measure what `instructions` consume.
+fn probe_transaction(
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    lookup_tables: &[AddressLookupTableAccount],
+) -> Result<VersionedTransaction, WorldError> {
+    let instructions = with_compute_budget(Some(MAX_COMPUTE_UNIT_LIMIT), None, instructions);
+    let message = if lookup_tables.is_empty() {
+        VersionedMessage::Legacy(Message::new(&instructions, Some(payer)))
+    } else {
+        VersionedMessage::V0(compile_v0_message(
+            payer,
+            &instructions,
+            lookup_tables,
+            Hash::default(),
+        )?)
+    };
+
+    Ok(VersionedTransaction {
+        signatures: vec![Signature::default(); message.header().num_required_signatures as usize],
+        message,
+    })
+}
+
+fn verify_signatures(transaction: &Transaction) -> Result<(), WorldError> {
+    let signers = transaction.message.signer_keys();
+    let valid = transaction.verify_with_results();
+
+    for ((signer, signature), valid) in signers.iter().zip(&transaction.signatures).zip(valid) {
+        if *signature == Signature::default() {
+            return Err(WorldError::MissingSignature(**signer));
+        }
+        if !valid {
+            return Err(WorldError::InvalidSignature(**signer));
+        }
+    }
+    Ok(())
+}
+
+fn compile_v0_message(
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    lookup_tables: &[AddressLookupTableAccount],
+    blockhash: Hash,
+) -> Result<v0::Message, WorldError> {
+    v0::Message::try_compile(payer, instructions, lookup_tables, blockhash)
+        .map_err(|err| WorldError::TransactionBuildError(err.to_string()))
+}
+
+#[cfg(test)]
+mod test_client {
+    use super::*;
+    use crate::instructions::write_to_world_ix;
+    use solana_sdk::compute_budget::ComputeBudgetInstruction;
+
+    #[test]
+    fn transaction_size_matches_serialized_transaction() {
+        let payer = solana_keypair::Keypair::new();
+        let ix = write_to_world_ix(payer.pubkey(), Pubkey::new_unique(), [7; 32], &[1; 64]);
+
+        let tx = sign_transaction(&payer, &[], std::slice::from_ref(&ix), Hash::default());
+
+        assert_eq!(
+            transaction_size(&payer.pubkey(), &[ix]),
+            bincode::serialize(&tx).unwrap().len(),
+            "Size doesn't match"
+        );
+    }
+
+    #[test]
+    fn blockhash_cache_expires_after_ttl() {
+        let cache = BlockhashCache::new(Duration::from_millis(20));
+        assert_eq!(cache.get(), None);
+
+        let hash = Hash::new_unique();
+        cache.set(hash);
+        assert_eq!(cache.get(), Some(hash));
+
+        thread::sleep(Duration::from_millis(30));
+        assert_eq!(cache.get(), None, "Stale blockhash was reused");
+
+        let refreshed = Hash::new_unique();
+        cache.set(refreshed);
+        assert_eq!(cache.get(), Some(refreshed));
+    }
+
+    #[test]
+    fn blockhash_cache_invalidates_on_blockhash_not_found() {
+        let cache = BlockhashCache::new(Duration::from_secs(60));
+        cache.set(Hash::new_unique());
+
+        let program_error: WorldError =
+            solana_sdk::transaction::TransactionError::AccountInUse.into();
+        assert!(!cache.invalidate_on(&program_error));
+        assert!(cache.get().is_some());
+
+        let expired: WorldError =
+            solana_sdk::transaction::TransactionError::BlockhashNotFound.into();
+        assert!(cache.invalidate_on(&expired));
+        assert_eq!(cache.get(), None);
+    }
+
+    #[test]
+    fn repeated_write_over_cached_blockhash_is_a_duplicate() {
+        let cache = BlockhashCache::new(Duration::from_secs(60));
+        let payer = solana_keypair::Keypair::new();
+        let state = Pubkey::new_unique();
+        let write = |data: &[u8], blockhash| {
+            let ix = write_to_world_ix(payer.pubkey(), state, [7; 32], data);
+            sign_versioned_transaction(&payer, &[], &[ix], blockhash, &[]).unwrap()
+        };
+
+        let blockhash = Hash::new_unique();
+        assert!(cache.mark_signed(&write(b"A", blockhash)));
+        assert!(cache.mark_signed(&write(b"B", blockhash)));
+        // The second A is byte-for-byte the first one; the node would drop it
+        // and the state would stay at B.
+        assert!(!cache.mark_signed(&write(b"A", blockhash)));
+
+        assert!(cache.mark_signed(&write(b"A", Hash::new_unique())));
+    }
+
+    #[test]
+    fn size_check_counts_compute_budget_instructions() {
+        let payer = Pubkey::new_unique();
+        let state = Pubkey::new_unique();
+        // The largest write that fits on its own, with a limit already set.
+        let limit = ComputeBudgetInstruction::set_compute_unit_limit(200_000);
+        let ixs = (0..MAX_TRANSACTION_SIZE)
+            .rev()
+            .map(|len| {
+                vec![
+                    limit.clone(),
+                    write_to_world_ix(payer, state, [7; 32], &vec![1; len]),
+                ]
+            })
+            .find(|ixs| fits_in_transaction(&payer, ixs))
+            .unwrap();
+
+        let client = WorldClient::with_config(
+            &RpcType::Localnet,
+            Validator::default_for(&RpcType::Localnet),
+            WorldClientConfig {
+                fee_strategy: FeeStrategy::Fixed(1),
+                ..WorldClientConfig::default()
+            },
+        );
+
+        assert!(client.fits_in_transaction(&payer, &ixs, RpcLayer::Ephemeral));
+        assert!(!client.fits_in_transaction(&payer, &ixs, RpcLayer::BaseLayer));
+    }
+
+    #[test]
+    fn ws_url_follows_rpc_url() {
+        let validator = Validator::default();
+
+        assert_eq!(
+            RpcType::Devnet.ws_url(RpcLayer::Ephemeral, &validator),
+            "wss://devnet-eu.magicblock.app/"
+        );
+        assert_eq!(
+            RpcType::Localnet.ws_url(RpcLayer::BaseLayer, &validator),
+            "ws://127.0.0.1:8900/"
+        );
+    }
+
+    #[test]
+    fn oversized_payload_does_not_fit() {
+        let payer = Pubkey::new_unique();
+        let small = write_to_world_ix(payer, Pubkey::new_unique(), [0; 32], &[0; 128]);
+        let large = write_to_world_ix(payer, Pubkey::new_unique(), [0; 32], &[0; 2048]);
+
+        assert!(fits_in_transaction(&payer, &[small]));
+        assert!(!fits_in_transaction(&payer, &[large]));
+    }
+
+    #[test]
+    fn lookup_tables_shrink_transactions() {
+        let payer = solana_keypair::Keypair::new();
+        let ixs = vec![
+            crate::instructions::delegate_account_ix(
+                payer.pubkey(),
+                Pubkey::new_unique(),
+                [1; 32],
+                &[0; 64],
+                Validator::default().identity,
+            ),
+            write_to_world_ix(payer.pubkey(), Pubkey::new_unique(), [2; 32], &[0; 64]),
+        ];
+        let tables = [AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: crate::lookup_table::MOJO_LOOKUP_ADDRESSES.to_vec(),
+        }];
+
+        let tx = sign_versioned_transaction(&payer, &[], &ixs, Hash::default(), &tables).unwrap();
+        let size = versioned_transaction_size(&payer.pubkey(), &ixs, &tables).unwrap();
+
+        assert_eq!(size, bincode::serialize(&tx).unwrap().len());
+        assert!(size < transaction_size(&payer.pubkey(), &ixs));
+    }
+
+    #[test]
+    fn packs_instructions_into_fewest_transactions() {
+        let payer = Pubkey::new_unique();
+        let ixs: Vec<Instruction> = (0..6)
+            .map(|_| write_to_world_ix(payer, Pubkey::new_unique(), [0; 32], &[0; 300]))
+            .collect();
+
+        let batches = pack_instructions(&payer, ixs.clone()).unwrap();
+
+        assert!(batches.len() > 1);
+        assert!(batches
+            .iter()
+            .all(|batch| fits_in_transaction(&payer, batch)));
+        assert_eq!(batches.concat(), ixs);
+
+        let large = write_to_world_ix(payer, Pubkey::new_unique(), [0; 32], &[0; 2048]);
+        assert!(pack_instructions(&payer, vec![large]).is_err());
     }
 }
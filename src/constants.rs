@@ -2,3 +2,7 @@ use solana_pubkey::pubkey;
 
 pub const PROGRAM_ID: solana_pubkey::Pubkey =
     pubkey!("7iMdvW8A4Tw3yxjbXjpx4b8LTW13EQLB4eTmPyqRvxzM");
+
+/// The delegation program ID.
+pub const DELEGATION_PROGRAM_ID: solana_pubkey::Pubkey =
+    pubkey!("DELeGGvXpWV2fqJUhqcF5ZSYMS4JTLjteaAMARRSaeSh");
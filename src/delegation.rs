@@ -0,0 +1,262 @@
+use bytemuck::{pod_read_unaligned, Pod, Zeroable};
+use solana_pubkey::Pubkey;
+
+use solana_account::Account;
+
+use crate::{
+    client::{AsyncWorldClient, RpcLayer, WorldClient},
+    error::WorldError,
+    pda::{find_delegation_metadata_pda, find_delegation_record_pda},
+    validator::Validator,
+};
+
+/// Accounts owned by the delegation program start with an 8 byte discriminator.
+const DISCRIMINATOR_LEN: usize = 8;
+
+/// Layout of the delegation record account, after its discriminator.
+#[repr(C)]
+#[derive(Pod, Zeroable, Clone, Copy, Debug, PartialEq)]
+pub struct DelegationRecord {
+    /// Validator allowed to commit the delegated account.
+    pub authority: [u8; 32],
+    /// Program that owned the account before delegation.
+    pub owner: [u8; 32],
+    pub delegation_slot: u64,
+    pub lamports: u64,
+    pub commit_frequency_ms: u64,
+}
+
+impl DelegationRecord {
+    pub const LEN: usize = core::mem::size_of::<DelegationRecord>();
+
+    pub fn from_account_data(data: &[u8]) -> Result<Self, WorldError> {
+        let expected = DISCRIMINATOR_LEN + Self::LEN;
+        if data.len() < expected {
+            return Err(WorldError::AccountSizeMismatch {
+                expected,
+                actual: data.len(),
+            });
+        }
+
+        Ok(pod_read_unaligned(&data[DISCRIMINATOR_LEN..expected]))
+    }
+}
+
+/// The borsh encoded delegation metadata account, after its discriminator.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DelegationMetadata {
+    /// Slot of the last commit that reached the base layer.
+    pub last_commit_slot: u64,
+    pub is_undelegatable: bool,
+    pub seeds: Vec<Vec<u8>>,
+    pub rent_payer: Pubkey,
+}
+
+impl DelegationMetadata {
+    pub fn from_account_data(data: &[u8]) -> Result<Self, WorldError> {
+        let mut reader = BorshReader {
+            data,
+            offset: DISCRIMINATOR_LEN,
+        };
+
+        let last_commit_slot = u64::from_le_bytes(reader.take()?);
+        let [is_undelegatable] = reader.take()?;
+
+        let seed_count = u32::from_le_bytes(reader.take()?);
+        let mut seeds = Vec::new();
+        for _ in 0..seed_count {
+            let len = u32::from_le_bytes(reader.take()?) as usize;
+            seeds.push(reader.take_slice(len)?.to_vec());
+        }
+
+        let rent_payer = Pubkey::new_from_array(reader.take()?);
+
+        Ok(Self {
+            last_commit_slot,
+            is_undelegatable: is_undelegatable != 0,
+            seeds,
+            rent_payer,
+        })
+    }
+}
+
+struct BorshReader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> BorshReader<'a> {
+    fn take_slice(&mut self, len: usize) -> Result<&'a [u8], WorldError> {
+        let end = self.offset + len;
+        let slice = self
+            .data
+            .get(self.offset..end)
+            .ok_or(WorldError::AccountSizeMismatch {
+                expected: end,
+                actual: self.data.len(),
+            })?;
+        self.offset = end;
+        Ok(slice)
+    }
+
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], WorldError> {
+        let mut bytes = [0u8; N];
+        bytes.copy_from_slice(self.take_slice(N)?);
+        Ok(bytes)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DelegationStatus {
+    pub account: Pubkey,
+    /// Validator identity recorded as the delegation authority.
+    pub authority: Pubkey,
+    /// The known validator matching `authority`, if any.
+    pub validator: Option<Validator>,
+    pub owner: Pubkey,
+    pub delegation_slot: u64,
+    pub commit_frequency_ms: u64,
+    pub last_commit_slot: u64,
+    pub is_undelegatable: bool,
+    pub rent_payer: Pubkey,
+}
+
+impl DelegationStatus {
+    pub fn new(account: Pubkey, record: &DelegationRecord, metadata: &DelegationMetadata) -> Self {
+        let authority = Pubkey::new_from_array(record.authority);
+
+        Self {
+            account,
+            authority,
+            validator: Validator::from_identity(&authority),
+            owner: Pubkey::new_from_array(record.owner),
+            delegation_slot: record.delegation_slot,
+            commit_frequency_ms: record.commit_frequency_ms,
+            last_commit_slot: metadata.last_commit_slot,
+            is_undelegatable: metadata.is_undelegatable,
+            rent_payer: metadata.rent_payer,
+        }
+    }
+}
+
+/// Reads the delegation record and metadata of `account` from the base layer.
+/// Returns `None` when the account is not delegated.
+pub fn fetch_delegation_status(
+    client: &WorldClient,
+    account: &Pubkey,
+) -> Result<Option<DelegationStatus>, WorldError> {
+    let (record_pda, _) = find_delegation_record_pda(account);
+    let (metadata_pda, _) = find_delegation_metadata_pda(account);
+
+    let accounts = client
+        .rpc(RpcLayer::BaseLayer)
+        .get_multiple_accounts_with_commitment(
+            &[record_pda, metadata_pda],
+            client.config.commitment,
+        )?
+        .value;
+
+    parse_delegation_status(account, &accounts)
+}
+
+/// [`fetch_delegation_status`] for many accounts, reading every record and
+/// metadata account in as few requests as possible. The result lines up
+/// with `accounts`.
+pub fn fetch_delegation_statuses(
+    client: &WorldClient,
+    accounts: &[Pubkey],
+) -> Result<Vec<Option<DelegationStatus>>, WorldError> {
+    let addresses: Vec<Pubkey> = accounts
+        .iter()
+        .flat_map(|account| {
+            [
+                find_delegation_record_pda(account).0,
+                find_delegation_metadata_pda(account).0,
+            ]
+        })
+        .collect();
+
+    let fetched = client.get_multiple_accounts(&addresses, RpcLayer::BaseLayer)?;
+    accounts
+        .iter()
+        .zip(fetched.chunks(2))
+        .map(|(account, pair)| parse_delegation_status(account, pair))
+        .collect()
+}
+
+pub async fn fetch_delegation_status_async(
+    client: &AsyncWorldClient,
+    account: &Pubkey,
+) -> Result<Option<DelegationStatus>, WorldError> {
+    let (record_pda, _) = find_delegation_record_pda(account);
+    let (metadata_pda, _) = find_delegation_metadata_pda(account);
+
+    let accounts = client
+        .rpc(RpcLayer::BaseLayer)
+        .get_multiple_accounts_with_commitment(
+            &[record_pda, metadata_pda],
+            client.config.commitment,
+        )
+        .await?
+        .value;
+
+    parse_delegation_status(account, &accounts)
+}
+
+fn parse_delegation_status(
+    account: &Pubkey,
+    accounts: &[Option<Account>],
+) -> Result<Option<DelegationStatus>, WorldError> {
+    match accounts {
+        [Some(record), Some(metadata)] => {
+            let record = DelegationRecord::from_account_data(&record.data)?;
+            let metadata = DelegationMetadata::from_account_data(&metadata.data)?;
+            Ok(Some(DelegationStatus::new(*account, &record, &metadata)))
+        }
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod test_delegation {
+    use super::*;
+    use crate::instructions::EU_VALIDATOR;
+
+    #[test]
+    fn parses_delegation_record() {
+        let record = DelegationRecord {
+            authority: EU_VALIDATOR.to_bytes(),
+            owner: [3; 32],
+            delegation_slot: 42,
+            lamports: 1_000,
+            commit_frequency_ms: 30_000,
+        };
+        let mut data = vec![9u8; DISCRIMINATOR_LEN];
+        data.extend_from_slice(bytemuck::bytes_of(&record));
+
+        assert_eq!(DelegationRecord::from_account_data(&data).unwrap(), record);
+        assert!(DelegationRecord::from_account_data(&data[..40]).is_err());
+    }
+
+    #[test]
+    fn parses_delegation_metadata() {
+        let rent_payer = Pubkey::new_unique();
+        let mut data = vec![0u8; DISCRIMINATOR_LEN];
+        data.extend_from_slice(&77u64.to_le_bytes());
+        data.push(1);
+        data.extend_from_slice(&2u32.to_le_bytes());
+        data.extend_from_slice(&3u32.to_le_bytes());
+        data.extend_from_slice(b"abc");
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.push(7);
+        data.extend_from_slice(rent_payer.as_ref());
+
+        let metadata = DelegationMetadata::from_account_data(&data).unwrap();
+
+        assert_eq!(metadata.last_commit_slot, 77);
+        assert!(metadata.is_undelegatable);
+        assert_eq!(metadata.seeds, vec![b"abc".to_vec(), vec![7]]);
+        assert_eq!(metadata.rent_payer, rent_payer);
+        assert!(DelegationMetadata::from_account_data(&data[..data.len() - 1]).is_err());
+    }
+}
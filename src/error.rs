@@ -1,9 +1,24 @@
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    rpc_custom_error::{
+        JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED, JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY,
+    },
+    rpc_request::RpcError,
+};
+use solana_instruction::error::InstructionError;
+use solana_pubkey::Pubkey;
+use solana_sdk::{signature::Signature, transaction::TransactionError};
 use thiserror::Error;
 
+use crate::simulation::SimulationReport;
+
 #[derive(Error, Debug)]
 pub enum WorldError {
     #[error("RPC Error: {0}")]
     RpcError(String),
+    /// The endpoint was unreachable, timed out or reported itself unhealthy.
+    #[error("Transient RPC Error: {0}")]
+    TransientRpcError(String),
     #[error("Serialization Error")]
     SerializationError,
     #[error("Image upload failed: {0}")]
@@ -28,4 +43,152 @@ pub enum WorldError {
     MetadataFetchError(String),
     #[error("Failed to deserialize asset: {0}")]
     AssetDeserializationError(String),
+    #[error("Upload failed: {0}")]
+    UploadError(String),
+    #[error("Subscription failed: {0}")]
+    SubscriptionError(String),
+    #[error("Transaction failed: {error}")]
+    TransactionError {
+        error: TransactionError,
+        /// The `Custom` code returned by the failing program, if any.
+        program_error: Option<u32>,
+    },
+    #[error("PDA mismatch: expected {expected}, found {found}")]
+    PdaMismatch { expected: Pubkey, found: Pubkey },
+    #[error("Failed to build transaction: {0}")]
+    TransactionBuildError(String),
+    #[error("Session error: {0}")]
+    SessionError(String),
+    #[error("Missing signature for {0}")]
+    MissingSignature(Pubkey),
+    #[error("Invalid signature for {0}")]
+    InvalidSignature(Pubkey),
+    #[error("Transaction {0} expired before it was confirmed")]
+    TransactionExpired(Signature),
+    #[error("Transaction too large: {size} bytes (max: {max} bytes)")]
+    TransactionTooLarge { size: usize, max: usize },
+    #[error("Account size mismatch: expected at least {expected} bytes, found {actual}")]
+    AccountSizeMismatch { expected: usize, actual: usize },
+    #[error("State is delegated to unknown validator {0}")]
+    UnknownValidator(Pubkey),
+    #[error("Account {0} is not delegated")]
+    NotDelegated(Pubkey),
+    #[error("Account {0} is already delegated")]
+    AlreadyDelegated(Pubkey),
+    #[error("Account {account} is owned by unexpected program {owner}")]
+    UnexpectedOwner { account: Pubkey, owner: Pubkey },
+    /// Returned in dry-run mode in place of a signature: the transaction
+    /// would have succeeded, but was only simulated.
+    #[error("Dry run: transaction simulated, not sent")]
+    DryRun(Box<SimulationReport>),
+}
+
+impl From<TransactionError> for WorldError {
+    fn from(error: TransactionError) -> Self {
+        let program_error = match error {
+            TransactionError::InstructionError(_, InstructionError::Custom(code)) => Some(code),
+            _ => None,
+        };
+
+        WorldError::TransactionError {
+            error,
+            program_error,
+        }
+    }
+}
+
+impl From<ClientError> for WorldError {
+    fn from(error: ClientError) -> Self {
+        if let Some(tx_error) = error.get_transaction_error() {
+            return tx_error.into();
+        }
+
+        let transient = match error.kind() {
+            ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_) => true,
+            ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. }) => matches!(
+                *code,
+                JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY
+                    | JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED
+            ),
+            _ => false,
+        };
+
+        if transient {
+            WorldError::TransientRpcError(error.to_string())
+        } else {
+            WorldError::RpcError(error.to_string())
+        }
+    }
+}
+
+impl WorldError {
+    /// Converts an error from `send_and_confirm_transaction` of `signature`.
+    /// The RPC client gives up with a plain `ForUser` message once the
+    /// blockhash expires without the transaction landing; that is reported as
+    /// [`WorldError::TransactionExpired`] so it is re-signed and retried.
+    pub(crate) fn from_confirm_error(error: ClientError, signature: Signature) -> Self {
+        match error.kind() {
+            ClientErrorKind::RpcError(RpcError::ForUser(message))
+                if message.starts_with(UNABLE_TO_CONFIRM) =>
+            {
+                WorldError::TransactionExpired(signature)
+            }
+            _ => error.into(),
+        }
+    }
+}
+
+/// Start of the message `send_and_confirm_transaction` fails with when the
+/// blockhash expires before the transaction is confirmed.
+const UNABLE_TO_CONFIRM: &str = "unable to confirm transaction";
+
+#[cfg(test)]
+mod test_error {
+    use super::*;
+
+    #[test]
+    fn custom_program_error_is_decoded() {
+        let error: WorldError =
+            TransactionError::InstructionError(0, InstructionError::Custom(6001)).into();
+
+        match error {
+            WorldError::TransactionError { program_error, .. } => {
+                assert_eq!(program_error, Some(6001), "Program error not decoded")
+            }
+            other => panic!("Unexpected error: {other}"),
+        }
+    }
+
+    #[test]
+    fn non_program_error_has_no_code() {
+        let error: WorldError = TransactionError::BlockhashNotFound.into();
+
+        assert!(matches!(
+            error,
+            WorldError::TransactionError {
+                error: TransactionError::BlockhashNotFound,
+                program_error: None,
+            }
+        ));
+    }
+
+    #[test]
+    fn confirmation_timeout_is_expiry() {
+        let signature = Signature::new_unique();
+        let timeout = ClientError::from(RpcError::ForUser(
+            "unable to confirm transaction. This can happen in situations such as \
+             transaction expiration and insufficient fee-payer funds"
+                .to_string(),
+        ));
+        let other = ClientError::from(RpcError::ForUser("bad params".to_string()));
+
+        assert!(matches!(
+            WorldError::from_confirm_error(timeout, signature),
+            WorldError::TransactionExpired(expired) if expired == signature
+        ));
+        assert!(matches!(
+            WorldError::from_confirm_error(other, signature),
+            WorldError::RpcError(_)
+        ));
+    }
 }
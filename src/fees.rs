@@ -0,0 +1,236 @@
+use solana_client::rpc_response::RpcPrioritizationFee;
+use solana_instruction::Instruction;
+use solana_pubkey::Pubkey;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk_ids::compute_budget::ID as COMPUTE_BUDGET_PROGRAM_ID;
+
+/// Most compute units a single transaction may request.
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+/// Headroom added on top of the units a simulation consumed, in percent.
+pub const COMPUTE_UNIT_MARGIN_PERCENT: u64 = 10;
+
+const SET_COMPUTE_UNIT_LIMIT_TAG: u8 = 2;
+const SET_COMPUTE_UNIT_PRICE_TAG: u8 = 3;
+
+/// How base-layer transactions price their compute units.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FeeStrategy {
+    #[default]
+    None,
+    /// A fixed price in micro-lamports per compute unit.
+    Fixed(u64),
+    /// The given percentile (0-100) of `getRecentPrioritizationFees` for the
+    /// accounts the transaction writes.
+    Percentile(u8),
+}
+
+/// Compute-unit limit requested by base-layer transactions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ComputeUnitLimit {
+    /// Leave the runtime default in place.
+    #[default]
+    Default,
+    Fixed(u32),
+    /// Simulate first and request what was consumed plus
+    /// [`COMPUTE_UNIT_MARGIN_PERCENT`].
+    Simulated,
+}
+
+pub fn percentile_fee(fees: &[RpcPrioritizationFee], percentile: u8) -> u64 {
+    let mut fees: Vec<u64> = fees.iter().map(|fee| fee.prioritization_fee).collect();
+    if fees.is_empty() {
+        return 0;
+    }
+    fees.sort_unstable();
+
+    let index = (fees.len() - 1) * percentile.min(100) as usize / 100;
+    fees[index]
+}
+
+pub fn compute_unit_limit_from_simulation(units_consumed: u64) -> u32 {
+    let limit = units_consumed + units_consumed * COMPUTE_UNIT_MARGIN_PERCENT / 100;
+    limit.min(MAX_COMPUTE_UNIT_LIMIT as u64) as u32
+}
+
+pub(crate) fn has_compute_unit_limit(instructions: &[Instruction]) -> bool {
+    has_compute_budget_ix(instructions, SET_COMPUTE_UNIT_LIMIT_TAG)
+}
+
+pub(crate) fn has_compute_unit_price(instructions: &[Instruction]) -> bool {
+    has_compute_budget_ix(instructions, SET_COMPUTE_UNIT_PRICE_TAG)
+}
+
+fn has_compute_budget_ix(instructions: &[Instruction], tag: u8) -> bool {
+    instructions
+        .iter()
+        .any(|ix| ix.program_id == COMPUTE_BUDGET_PROGRAM_ID && ix.data.first() == Some(&tag))
+}
+
+pub(crate) fn writable_accounts(instructions: &[Instruction]) -> Vec<Pubkey> {
+    let mut accounts: Vec<Pubkey> = instructions
+        .iter()
+        .flat_map(|ix| &ix.accounts)
+        .filter(|meta| meta.is_writable)
+        .map(|meta| meta.pubkey)
+        .collect();
+    accounts.sort_unstable();
+    accounts.dedup();
+    accounts
+}
+
+/// Prepends the compute-budget instructions to `instructions`. A zero price is
+/// left out since it would not change anything.
+pub(crate) fn with_compute_budget(
+    limit: Option<u32>,
+    price: Option<u64>,
+    instructions: &[Instruction],
+) -> Vec<Instruction> {
+    limit
+        .map(ComputeBudgetInstruction::set_compute_unit_limit)
+        .into_iter()
+        .chain(
+            price
+                .filter(|price| *price > 0)
+                .map(ComputeBudgetInstruction::set_compute_unit_price),
+        )
+        .chain(instructions.iter().cloned())
+        .collect()
+}
+
+/// The compute-budget instructions a base-layer transaction still needs,
+/// worked out from the config before any RPC call. Each client runs the
+/// simulation and fee lookup the plan asks for and hands the answers to
+/// [`BudgetPlan::apply`].
+pub(crate) struct BudgetPlan {
+    limit: Option<u32>,
+    price: Option<u64>,
+    /// The limit comes from simulating the transaction.
+    pub simulate: bool,
+    /// The price is this percentile of recent prioritization fees.
+    pub fee_percentile: Option<u8>,
+}
+
+impl BudgetPlan {
+    /// Instructions that already set a limit or price keep their own.
+    pub fn new(
+        compute_unit_limit: ComputeUnitLimit,
+        fee_strategy: FeeStrategy,
+        instructions: &[Instruction],
+    ) -> Self {
+        let mut plan = Self {
+            limit: None,
+            price: None,
+            simulate: false,
+            fee_percentile: None,
+        };
+
+        if !has_compute_unit_limit(instructions) {
+            match compute_unit_limit {
+                ComputeUnitLimit::Default => {}
+                ComputeUnitLimit::Fixed(limit) => plan.limit = Some(limit),
+                ComputeUnitLimit::Simulated => plan.simulate = true,
+            }
+        }
+        if !has_compute_unit_price(instructions) {
+            match fee_strategy {
+                FeeStrategy::None => {}
+                FeeStrategy::Fixed(price) => plan.price = Some(price),
+                FeeStrategy::Percentile(percentile) => plan.fee_percentile = Some(percentile),
+            }
+        }
+        plan
+    }
+
+    /// Prepends the planned instructions, given the units the simulation
+    /// consumed and the recent fees, where the plan asked for them.
+    pub fn apply(
+        self,
+        units_consumed: Option<u64>,
+        fees: &[RpcPrioritizationFee],
+        instructions: &[Instruction],
+    ) -> Vec<Instruction> {
+        let limit = if self.simulate {
+            units_consumed.map(compute_unit_limit_from_simulation)
+        } else {
+            self.limit
+        };
+        let price = match self.fee_percentile {
+            Some(percentile) => Some(percentile_fee(fees, percentile)),
+            None => self.price,
+        };
+        with_compute_budget(limit, price, instructions)
+    }
+
+    /// `instructions` with every planned budget instruction in place, for
+    /// size checks made before the simulation and fee lookup run.
+    pub fn reserve(&self, instructions: &[Instruction]) -> Vec<Instruction> {
+        let limit = (self.simulate || self.limit.is_some()).then_some(MAX_COMPUTE_UNIT_LIMIT);
+        let price = (self.fee_percentile.is_some() || self.price.is_some()).then_some(u64::MAX);
+        with_compute_budget(limit, price, instructions)
+    }
+}
+
+#[cfg(test)]
+mod test_fees {
+    use super::*;
+
+    #[test]
+    fn picks_percentile_fee() {
+        let fees: Vec<RpcPrioritizationFee> = [40, 0, 10, 30, 20]
+            .into_iter()
+            .map(|prioritization_fee| RpcPrioritizationFee {
+                slot: 1,
+                prioritization_fee,
+            })
+            .collect();
+
+        assert_eq!(percentile_fee(&fees, 0), 0);
+        assert_eq!(percentile_fee(&fees, 50), 20);
+        assert_eq!(percentile_fee(&fees, 100), 40);
+        assert_eq!(percentile_fee(&[], 75), 0);
+    }
+
+    #[test]
+    fn detects_existing_budget_instructions() {
+        let ixs = with_compute_budget(Some(compute_unit_limit_from_simulation(10_000)), None, &[]);
+
+        assert!(has_compute_unit_limit(&ixs));
+        assert!(!has_compute_unit_price(&ixs));
+        assert_eq!(
+            ixs[0],
+            ComputeBudgetInstruction::set_compute_unit_limit(11_000)
+        );
+        assert_eq!(
+            compute_unit_limit_from_simulation(u64::MAX / 200),
+            MAX_COMPUTE_UNIT_LIMIT
+        );
+    }
+
+    #[test]
+    fn budget_plan_keeps_instruction_budget() {
+        let price = ComputeBudgetInstruction::set_compute_unit_price(5);
+        let plan = BudgetPlan::new(
+            ComputeUnitLimit::Simulated,
+            FeeStrategy::Percentile(50),
+            std::slice::from_ref(&price),
+        );
+
+        assert!(plan.simulate);
+        assert_eq!(
+            plan.reserve(std::slice::from_ref(&price)),
+            vec![
+                ComputeBudgetInstruction::set_compute_unit_limit(MAX_COMPUTE_UNIT_LIMIT),
+                price.clone()
+            ]
+        );
+        assert_eq!(plan.fee_percentile, None);
+        assert_eq!(
+            plan.apply(Some(10_000), &[], std::slice::from_ref(&price)),
+            vec![
+                ComputeBudgetInstruction::set_compute_unit_limit(11_000),
+                price
+            ]
+        );
+    }
+}
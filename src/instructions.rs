@@ -5,36 +5,34 @@ use solana_system_interface::program::ID as SYSTEM_PROGRAM_ID;
 
 use crate::{
     constants::PROGRAM_ID,
-    mojo_types::{GenIxHandler, MojoInstructions},
+    mojo_types::{GenIxHandler, MojoInstructions, SessionToken},
+    patch::{encode_patches, StatePatch},
+    pda::{
+        find_buffer_pda, find_commit_record_pda, find_commit_state_pda,
+        find_delegation_metadata_pda, find_delegation_record_pda, find_session_pda,
+    },
 };
 
-/// The delegation program ID.
-pub const DELEGATION_PROGRAM_ID: Pubkey = pubkey!("DELeGGvXpWV2fqJUhqcF5ZSYMS4JTLjteaAMARRSaeSh");
+pub use crate::{
+    constants::DELEGATION_PROGRAM_ID,
+    pda::{BUFFER, COMMIT_RECORD, COMMIT_STATE, DELEGATION_METADATA, DELEGATION_RECORD, SESSION},
+};
 
 pub const EU_VALIDATOR: Pubkey = pubkey!("MEUGGrYPxKk17hCr7wpT6s8dtNokZj5U2L57vjYMS8e");
 
+pub const US_VALIDATOR: Pubkey = pubkey!("MUS3hc9TCw4cGC12vHNoYcCGzJG1txjgQLZWVoeNHNd");
+
+pub const ASIA_VALIDATOR: Pubkey = pubkey!("MAS1Dt9qreoRMQ14YQuhg8UTZMMzDdKhmkZMECCzk57");
+
+/// Identity of a locally run ephemeral validator.
+pub const LOCAL_VALIDATOR: Pubkey = pubkey!("mAGicPQYBMvcYveUZA5F5UNNwyHvfYh5xkLS2Fr1mev");
+
 /// The magic program ID.
 pub const MAGIC_PROGRAM_ID: Pubkey = pubkey!("Magic11111111111111111111111111111111111111");
 
 /// The magic context ID.
 pub const MAGIC_CONTEXT_ID: Pubkey = pubkey!("MagicContext1111111111111111111111111111111");
 
-///
-/// The seed of the authority account PDA.
-pub const DELEGATION_RECORD: &[u8] = b"delegation";
-
-/// The account to store the delegated account seeds.
-pub const DELEGATION_METADATA: &[u8] = b"delegation-metadata";
-
-/// The seed of the buffer account PDA.
-pub const BUFFER: &[u8] = b"buffer";
-
-/// The seed of the committed state PDA.
-pub const COMMIT_STATE: &[u8] = b"state-diff";
-
-/// The seed of a commit state record PDA.
-pub const COMMIT_RECORD: &[u8] = b"commit-state-record";
-
 /// The discriminator for the external undelegate instruction.
 pub const EXTERNAL_UNDELEGATE_DISCRIMINATOR: [u8; 8] = [196, 28, 41, 206, 48, 37, 51, 167];
 
@@ -81,21 +79,17 @@ pub fn delegate_account_ix(
     account: Pubkey,
     seed_hash: [u8; 32],
     state_data: &[u8],
+    validator: Pubkey,
 ) -> Instruction {
     let mut handler = GenIxHandler::new((state_data.len() as u64).to_le_bytes());
     handler.seeds.copy_from_slice(&seed_hash);
 
-    let buffer_account = Pubkey::find_program_address(&[BUFFER, account.as_ref()], &PROGRAM_ID).0;
+    let buffer_account = find_buffer_pda(&account).0;
 
     // Derive delegation_record PDA: ["delegation", account_pubkey]
-    let delegation_record =
-        Pubkey::find_program_address(&[b"delegation", account.as_ref()], &DELEGATION_PROGRAM_ID).0;
+    let delegation_record = find_delegation_record_pda(&account).0;
 
-    let delegation_metadata = Pubkey::find_program_address(
-        &[b"delegation-metadata", account.as_ref()],
-        &DELEGATION_PROGRAM_ID,
-    )
-    .0;
+    let delegation_metadata = find_delegation_metadata_pda(&account).0;
 
     let data = encode_instruction(MojoInstructions::DelegateAccount, &handler, state_data);
 
@@ -110,9 +104,9 @@ pub fn delegate_account_ix(
             AccountMeta::new(delegation_metadata, false), // delegation metadata
             AccountMeta::new(SYSTEM_PROGRAM_ID, false), // system program
             AccountMeta::new(DELEGATION_PROGRAM_ID, false), // Delegation Program ID
-            AccountMeta::new(EU_VALIDATOR, false),   // Europe Validator
+            AccountMeta::new(validator, false),      // ER validator
         ],
-        data: data,
+        data,
     }
 }
 
@@ -142,3 +136,218 @@ pub fn write_to_world_ix(
         data,
     }
 }
+
+/// Like [`write_to_world_ix`], but only sends the changed byte ranges. The
+/// handler carries the full state size so the program can check the account.
+pub fn patch_state_ix(
+    payer: Pubkey,
+    account: Pubkey,
+    seed_hash: [u8; 32],
+    state_size: usize,
+    patches: &[StatePatch],
+) -> Instruction {
+    let mut handler = GenIxHandler::new((state_size as u64).to_le_bytes());
+    handler.seeds.copy_from_slice(&seed_hash);
+
+    let data = encode_instruction(
+        MojoInstructions::PatchDelegatedAccount,
+        &handler,
+        &encode_patches(patches),
+    );
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new(account, false),
+            AccountMeta::new_readonly(MAGIC_CONTEXT_ID, false),
+            AccountMeta::new_readonly(MAGIC_PROGRAM_ID, false),
+        ],
+        data,
+    }
+}
+
+pub fn commit_state_ix(
+    payer: Pubkey,
+    account: Pubkey,
+    seed_hash: [u8; 32],
+    state_size: usize,
+) -> Instruction {
+    let mut handler = GenIxHandler::new((state_size as u64).to_le_bytes());
+    handler.seeds.copy_from_slice(&seed_hash);
+
+    let data = encode_instruction(MojoInstructions::Commit, &handler, &[]);
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new(account, false),
+            AccountMeta::new(MAGIC_CONTEXT_ID, false), // commits are scheduled into the context
+            AccountMeta::new_readonly(MAGIC_PROGRAM_ID, false),
+        ],
+        data,
+    }
+}
+
+pub fn undelegate_account_ix(
+    payer: Pubkey,
+    account: Pubkey,
+    seed_hash: [u8; 32],
+    state_size: usize,
+) -> Instruction {
+    let mut handler = GenIxHandler::new((state_size as u64).to_le_bytes());
+    handler.seeds.copy_from_slice(&seed_hash);
+
+    let buffer_account = find_buffer_pda(&account).0;
+    let commit_state = find_commit_state_pda(&account).0;
+    let commit_record = find_commit_record_pda(&account).0;
+    let delegation_record = find_delegation_record_pda(&account).0;
+    let delegation_metadata = find_delegation_metadata_pda(&account).0;
+
+    // The delegation program calls back into PROGRAM_ID with this discriminator
+    // once the account is handed back, so the program receives it up front.
+    let data = encode_instruction(
+        MojoInstructions::UndelegateAccount,
+        &handler,
+        &EXTERNAL_UNDELEGATE_DISCRIMINATOR,
+    );
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(payer, true),                       // creator/payer
+            AccountMeta::new(account, false),                    // delegated account
+            AccountMeta::new_readonly(PROGRAM_ID, false),        // owner program
+            AccountMeta::new(buffer_account, false),             // undelegate buffer PDA
+            AccountMeta::new(commit_state, false),               // committed state PDA
+            AccountMeta::new(commit_record, false),              // commit state record PDA
+            AccountMeta::new(delegation_record, false),          // delegation record
+            AccountMeta::new(delegation_metadata, false),        // delegation metadata
+            AccountMeta::new(MAGIC_CONTEXT_ID, false),           // magic context
+            AccountMeta::new_readonly(MAGIC_PROGRAM_ID, false),  // magic program
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false), // system program
+            AccountMeta::new_readonly(DELEGATION_PROGRAM_ID, false), // delegation program
+        ],
+        data,
+    }
+}
+
+/// Creates the session token letting `session_key` write `owner`'s states in
+/// `world` until `valid_until`.
+pub fn authorize_session_ix(
+    owner: Pubkey,
+    world: Pubkey,
+    session_key: Pubkey,
+    valid_until: i64,
+) -> Instruction {
+    let (session_token, _) = find_session_pda(&world, &owner, &session_key);
+    let token = SessionToken {
+        owner: owner.to_bytes(),
+        session_key: session_key.to_bytes(),
+        world: world.to_bytes(),
+        valid_until,
+    };
+
+    let handler = GenIxHandler::new((SessionToken::LEN as u64).to_le_bytes());
+    let data = encode_instruction(
+        MojoInstructions::AuthorizeSession,
+        &handler,
+        bytemuck::bytes_of(&token),
+    );
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(owner, true),
+            AccountMeta::new(session_token, false),
+            AccountMeta::new_readonly(world, false),
+            AccountMeta::new_readonly(session_key, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        ],
+        data,
+    }
+}
+
+/// Closes a session token and returns its rent to `owner`.
+pub fn revoke_session_ix(owner: Pubkey, world: Pubkey, session_key: Pubkey) -> Instruction {
+    let (session_token, _) = find_session_pda(&world, &owner, &session_key);
+
+    let handler = GenIxHandler::new((SessionToken::LEN as u64).to_le_bytes());
+    let data = encode_instruction(MojoInstructions::RevokeSession, &handler, &[]);
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(owner, true),
+            AccountMeta::new(session_token, false),
+        ],
+        data,
+    }
+}
+
+/// [`write_to_world_ix`] signed by a session key. The state still derives
+/// from `owner` through `seed_hash`; the program checks the session token.
+pub fn write_with_session_ix(
+    session_key: Pubkey,
+    owner: Pubkey,
+    world: Pubkey,
+    account: Pubkey,
+    seed_hash: [u8; 32],
+    state_data: &[u8],
+) -> Instruction {
+    let (session_token, _) = find_session_pda(&world, &owner, &session_key);
+
+    let mut handler = GenIxHandler::new((state_data.len() as u64).to_le_bytes());
+    handler.seeds.copy_from_slice(&seed_hash);
+
+    let data = encode_instruction(MojoInstructions::UpdateWithSession, &handler, state_data);
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(session_key, true),
+            AccountMeta::new(account, false),
+            AccountMeta::new_readonly(session_token, false),
+            AccountMeta::new_readonly(owner, false),
+            AccountMeta::new_readonly(MAGIC_CONTEXT_ID, false),
+            AccountMeta::new_readonly(MAGIC_PROGRAM_ID, false),
+        ],
+        data,
+    }
+}
+
+/// [`patch_state_ix`] signed by a session key.
+pub fn patch_with_session_ix(
+    session_key: Pubkey,
+    owner: Pubkey,
+    world: Pubkey,
+    account: Pubkey,
+    seed_hash: [u8; 32],
+    state_size: usize,
+    patches: &[StatePatch],
+) -> Instruction {
+    let (session_token, _) = find_session_pda(&world, &owner, &session_key);
+
+    let mut handler = GenIxHandler::new((state_size as u64).to_le_bytes());
+    handler.seeds.copy_from_slice(&seed_hash);
+
+    let data = encode_instruction(
+        MojoInstructions::PatchWithSession,
+        &handler,
+        &encode_patches(patches),
+    );
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(session_key, true),
+            AccountMeta::new(account, false),
+            AccountMeta::new_readonly(session_token, false),
+            AccountMeta::new_readonly(owner, false),
+            AccountMeta::new_readonly(MAGIC_CONTEXT_ID, false),
+            AccountMeta::new_readonly(MAGIC_PROGRAM_ID, false),
+        ],
+        data,
+    }
+}
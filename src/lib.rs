@@ -1,9 +1,20 @@
 pub mod client;
 pub mod constants;
+pub mod delegation;
 pub mod error;
+pub mod fees;
 pub mod instructions;
+pub mod lookup_table;
 pub mod m_macro;
 pub mod mojo_types;
+pub mod offline;
+pub mod patch;
 pub mod pda;
 pub mod profile;
+pub mod retry;
+pub mod session;
+pub mod simulation;
+pub mod subscription;
+pub mod tracker;
+pub mod validator;
 pub mod world;
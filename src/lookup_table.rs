@@ -0,0 +1,127 @@
+use solana_address_lookup_table_interface::{
+    instruction::{
+        create_lookup_table as create_lookup_table_ix,
+        extend_lookup_table as extend_lookup_table_ix,
+    },
+    state::AddressLookupTable,
+};
+use solana_instruction::Instruction;
+use solana_message::AddressLookupTableAccount;
+use solana_pubkey::Pubkey;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_signer::Signer;
+use solana_system_interface::program::ID as SYSTEM_PROGRAM_ID;
+
+use crate::{
+    client::{RpcLayer, WorldClient},
+    constants::{DELEGATION_PROGRAM_ID, PROGRAM_ID},
+    error::WorldError,
+    instructions::{MAGIC_CONTEXT_ID, MAGIC_PROGRAM_ID},
+};
+
+/// Accounts nearly every mojo transaction references.
+pub const MOJO_LOOKUP_ADDRESSES: [Pubkey; 5] = [
+    PROGRAM_ID,
+    DELEGATION_PROGRAM_ID,
+    MAGIC_PROGRAM_ID,
+    MAGIC_CONTEXT_ID,
+    SYSTEM_PROGRAM_ID,
+];
+
+/// Addresses added per extend transaction, to stay within the size limit.
+const MAX_ADDRESSES_PER_EXTEND: usize = 20;
+
+/// Creates a lookup table owned by `authority` holding `addresses`. The table
+/// can only be used from the slot after its last extension.
+pub fn create_lookup_table(
+    client: &WorldClient,
+    authority: &impl Signer,
+    addresses: &[Pubkey],
+) -> Result<AddressLookupTableAccount, WorldError> {
+    let recent_slot = client
+        .rpc(RpcLayer::BaseLayer)
+        .get_slot_with_commitment(CommitmentConfig::finalized())?;
+    let (create_ix, key) =
+        create_lookup_table_ix(authority.pubkey(), authority.pubkey(), recent_slot);
+
+    let mut chunks = addresses.chunks(MAX_ADDRESSES_PER_EXTEND);
+    let mut ixs = vec![create_ix];
+    if let Some(first) = chunks.next() {
+        ixs.push(extend_ix(&key, authority, first));
+    }
+    client.send_ixs(authority, ixs, RpcLayer::BaseLayer)?;
+
+    for chunk in chunks {
+        client.send_ixs(
+            authority,
+            vec![extend_ix(&key, authority, chunk)],
+            RpcLayer::BaseLayer,
+        )?;
+    }
+
+    Ok(AddressLookupTableAccount {
+        key,
+        addresses: addresses.to_vec(),
+    })
+}
+
+/// Creates a lookup table holding [`MOJO_LOOKUP_ADDRESSES`].
+pub fn create_mojo_lookup_table(
+    client: &WorldClient,
+    authority: &impl Signer,
+) -> Result<AddressLookupTableAccount, WorldError> {
+    create_lookup_table(client, authority, &MOJO_LOOKUP_ADDRESSES)
+}
+
+/// Adds whichever of `addresses` the table does not hold yet.
+pub fn extend_lookup_table(
+    client: &WorldClient,
+    authority: &impl Signer,
+    table: &Pubkey,
+    addresses: &[Pubkey],
+) -> Result<AddressLookupTableAccount, WorldError> {
+    let mut lookup_table = fetch_lookup_table(client, table)?;
+
+    let mut missing: Vec<Pubkey> = Vec::new();
+    for address in addresses {
+        if !lookup_table.addresses.contains(address) && !missing.contains(address) {
+            missing.push(*address);
+        }
+    }
+
+    for chunk in missing.chunks(MAX_ADDRESSES_PER_EXTEND) {
+        client.send_ixs(
+            authority,
+            vec![extend_ix(table, authority, chunk)],
+            RpcLayer::BaseLayer,
+        )?;
+    }
+
+    lookup_table.addresses.extend(missing);
+    Ok(lookup_table)
+}
+
+pub fn fetch_lookup_table(
+    client: &WorldClient,
+    table: &Pubkey,
+) -> Result<AddressLookupTableAccount, WorldError> {
+    let account = client
+        .get_account(table, RpcLayer::BaseLayer)?
+        .ok_or_else(|| WorldError::AccountNotFound(table.to_string()))?;
+    let lookup_table = AddressLookupTable::deserialize(&account.data)
+        .map_err(|_| WorldError::SerializationError)?;
+
+    Ok(AddressLookupTableAccount {
+        key: *table,
+        addresses: lookup_table.addresses.to_vec(),
+    })
+}
+
+fn extend_ix(table: &Pubkey, authority: &impl Signer, addresses: &[Pubkey]) -> Instruction {
+    extend_lookup_table_ix(
+        *table,
+        authority.pubkey(),
+        Some(authority.pubkey()),
+        addresses.to_vec(),
+    )
+}
@@ -46,6 +46,7 @@ macro_rules! mojo_enum {
 }
 
 #[cfg(test)]
+#[allow(clippy::wrong_self_convention)]
 mod test_macro {
 
     #[test]
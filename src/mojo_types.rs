@@ -10,6 +10,15 @@ pub enum MojoInstructions {
     Commit = 2,
     UpdateDelegatedAccount = 3,
     UndelegateAccount = 4,
+    /// Overwrites byte ranges of a delegated account; the payload is
+    /// [`encode_patches`](crate::patch::encode_patches) output.
+    PatchDelegatedAccount = 5,
+    AuthorizeSession = 6,
+    RevokeSession = 7,
+    /// Like `UpdateDelegatedAccount`, signed by a session key instead of the owner.
+    UpdateWithSession = 8,
+    /// Like `PatchDelegatedAccount`, signed by a session key instead of the owner.
+    PatchWithSession = 9,
 }
 
 #[repr(C)]
@@ -33,3 +42,18 @@ impl GenIxHandler {
         bytemuck::bytes_of(self).to_vec()
     }
 }
+
+/// Grants `session_key` write access to the owner's states in `world` until
+/// `valid_until` (unix seconds).
+#[repr(C)]
+#[derive(Pod, Zeroable, Clone, Copy, Debug, PartialEq)]
+pub struct SessionToken {
+    pub owner: [u8; 32],
+    pub session_key: [u8; 32],
+    pub world: [u8; 32],
+    pub valid_until: i64,
+}
+
+impl SessionToken {
+    pub const LEN: usize = core::mem::size_of::<SessionToken>();
+}
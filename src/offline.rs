@@ -0,0 +1,126 @@
+use solana_instruction::Instruction;
+use solana_message::Message;
+use solana_pubkey::Pubkey;
+use solana_sdk::{hash::Hash, signature::Signature};
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+
+use crate::{client::RpcLayer, error::WorldError, validator::Validator};
+
+/// A transaction built for signing outside the SDK, e.g. by a browser wallet
+/// or an HSM. Have each of [`missing_signers`](Self::missing_signers) sign
+/// [`message_data`](Self::message_data), add the signatures and submit it with
+/// [`World::submit_signed`](crate::world::World::submit_signed).
+#[derive(Clone, Debug)]
+pub struct UnsignedTransaction {
+    pub transaction: Transaction,
+    /// Layer the transaction has to be submitted to.
+    pub layer: RpcLayer,
+    /// Validator whose ER an ephemeral transaction has to be submitted to.
+    pub validator: Validator,
+}
+
+impl UnsignedTransaction {
+    pub fn new(
+        payer: &Pubkey,
+        instructions: &[Instruction],
+        blockhash: Hash,
+        layer: RpcLayer,
+        validator: Validator,
+    ) -> Self {
+        let message = Message::new_with_blockhash(instructions, Some(payer), &blockhash);
+        Self {
+            transaction: Transaction::new_unsigned(message),
+            layer,
+            validator,
+        }
+    }
+
+    pub fn message(&self) -> &Message {
+        &self.transaction.message
+    }
+
+    /// The bytes every signer has to sign.
+    pub fn message_data(&self) -> Vec<u8> {
+        self.transaction.message_data()
+    }
+
+    /// Every account that has to sign, fee payer first.
+    pub fn required_signers(&self) -> &[Pubkey] {
+        let message = self.message();
+        &message.account_keys[..message.header.num_required_signatures as usize]
+    }
+
+    pub fn missing_signers(&self) -> Vec<Pubkey> {
+        self.required_signers()
+            .iter()
+            .zip(&self.transaction.signatures)
+            .filter(|(_, signature)| **signature == Signature::default())
+            .map(|(signer, _)| *signer)
+            .collect()
+    }
+
+    /// Adds a signature produced elsewhere, checking it against the message.
+    pub fn add_signature(
+        &mut self,
+        signer: &Pubkey,
+        signature: Signature,
+    ) -> Result<(), WorldError> {
+        let index = self
+            .required_signers()
+            .iter()
+            .position(|key| key == signer)
+            .ok_or(WorldError::InvalidSignature(*signer))?;
+        if !signature.verify(signer.as_ref(), &self.message_data()) {
+            return Err(WorldError::InvalidSignature(*signer));
+        }
+
+        self.transaction.signatures[index] = signature;
+        Ok(())
+    }
+
+    /// Signs with a key the SDK holds, such as a freshly generated account.
+    pub fn partial_sign(&mut self, signer: &impl Signer) -> Result<(), WorldError> {
+        let signature = signer.sign_message(&self.message_data());
+        self.add_signature(&signer.pubkey(), signature)
+    }
+
+    pub fn is_signed(&self) -> bool {
+        self.missing_signers().is_empty()
+    }
+
+    pub fn into_transaction(self) -> Transaction {
+        self.transaction
+    }
+}
+
+#[cfg(test)]
+mod test_offline {
+    use super::*;
+    use crate::instructions::write_to_world_ix;
+    use solana_keypair::Keypair;
+
+    #[test]
+    fn collects_signatures_made_elsewhere() {
+        let payer = Keypair::new();
+        let ix = write_to_world_ix(payer.pubkey(), Pubkey::new_unique(), [0; 32], &[1; 16]);
+        let mut unsigned = UnsignedTransaction::new(
+            &payer.pubkey(),
+            &[ix],
+            Hash::default(),
+            RpcLayer::Ephemeral,
+            Validator::EU,
+        );
+
+        assert_eq!(unsigned.missing_signers(), vec![payer.pubkey()]);
+
+        let forged = Keypair::new().sign_message(&unsigned.message_data());
+        assert!(unsigned.add_signature(&payer.pubkey(), forged).is_err());
+
+        let signature = payer.sign_message(&unsigned.message_data());
+        unsigned.add_signature(&payer.pubkey(), signature).unwrap();
+
+        assert!(unsigned.is_signed());
+        assert!(unsigned.into_transaction().verify().is_ok());
+    }
+}
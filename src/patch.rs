@@ -0,0 +1,160 @@
+use bytemuck::bytes_of;
+
+use crate::{error::WorldError, world::MojoState};
+
+/// Bytes of framing each patch adds to an instruction: its offset and length.
+pub const PATCH_HEADER_LEN: usize = 8;
+
+/// A run of bytes to overwrite at `offset` within a state account.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StatePatch {
+    pub offset: u32,
+    pub bytes: Vec<u8>,
+}
+
+impl StatePatch {
+    pub fn new(offset: u32, bytes: impl Into<Vec<u8>>) -> Self {
+        Self {
+            offset,
+            bytes: bytes.into(),
+        }
+    }
+
+    /// Patch overwriting a single field, e.g.
+    /// `StatePatch::field(core::mem::offset_of!(Player, score), &score)`.
+    pub fn field<F: MojoState>(offset: usize, value: &F) -> Self {
+        Self::new(offset as u32, bytes_of(value))
+    }
+}
+
+/// Byte ranges that differ between two equally sized buffers. Runs separated
+/// by fewer bytes than a patch header are merged, since resending the gap is
+/// cheaper than framing another patch.
+pub fn diff(old: &[u8], new: &[u8]) -> Result<Vec<StatePatch>, WorldError> {
+    if old.len() != new.len() {
+        return Err(WorldError::AccountSizeMismatch {
+            expected: old.len(),
+            actual: new.len(),
+        });
+    }
+
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for (index, (a, b)) in old.iter().zip(new).enumerate() {
+        if a == b {
+            continue;
+        }
+        match ranges.last_mut() {
+            Some((_, end)) if index - *end <= PATCH_HEADER_LEN => *end = index + 1,
+            _ => ranges.push((index, index + 1)),
+        }
+    }
+
+    Ok(ranges
+        .into_iter()
+        .map(|(start, end)| StatePatch::new(start as u32, &new[start..end]))
+        .collect())
+}
+
+pub fn diff_states<T: MojoState>(old: &T, new: &T) -> Vec<StatePatch> {
+    diff(bytes_of(old), bytes_of(new)).expect("values of one type have the same size")
+}
+
+/// Instruction payload for a patch write: a `u32` patch count, then for each
+/// patch its `u32` offset, `u32` length and bytes, all little endian.
+pub fn encode_patches(patches: &[StatePatch]) -> Vec<u8> {
+    let len = 4 + patches
+        .iter()
+        .map(|patch| PATCH_HEADER_LEN + patch.bytes.len())
+        .sum::<usize>();
+
+    let mut bytes = Vec::with_capacity(len);
+    bytes.extend_from_slice(&(patches.len() as u32).to_le_bytes());
+    for patch in patches {
+        bytes.extend_from_slice(&patch.offset.to_le_bytes());
+        bytes.extend_from_slice(&(patch.bytes.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&patch.bytes);
+    }
+    bytes
+}
+
+pub fn decode_patches(data: &[u8]) -> Result<Vec<StatePatch>, WorldError> {
+    let mut cursor = data;
+    let count = take_u32(&mut cursor)?;
+
+    let mut patches = Vec::new();
+    for _ in 0..count {
+        let offset = take_u32(&mut cursor)?;
+        let len = take_u32(&mut cursor)? as usize;
+        patches.push(StatePatch::new(offset, take(&mut cursor, len)?));
+    }
+    Ok(patches)
+}
+
+fn take<'a>(cursor: &mut &'a [u8], len: usize) -> Result<&'a [u8], WorldError> {
+    if cursor.len() < len {
+        return Err(WorldError::SerializationError);
+    }
+    let (head, rest) = cursor.split_at(len);
+    *cursor = rest;
+    Ok(head)
+}
+
+fn take_u32(cursor: &mut &[u8]) -> Result<u32, WorldError> {
+    let bytes = take(cursor, 4)?;
+    Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// Applies patches the way the program does: in order, each one bounds
+/// checked against the account data before anything is written.
+pub fn apply_patches(data: &mut [u8], patches: &[StatePatch]) -> Result<(), WorldError> {
+    for patch in patches {
+        let end = patch.offset as usize + patch.bytes.len();
+        if end > data.len() {
+            return Err(WorldError::AccountSizeMismatch {
+                expected: end,
+                actual: data.len(),
+            });
+        }
+    }
+
+    for patch in patches {
+        let start = patch.offset as usize;
+        data[start..start + patch.bytes.len()].copy_from_slice(&patch.bytes);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test_patch {
+    use super::*;
+
+    #[test]
+    fn diff_applies_back_to_new_state() {
+        let old = [0u8; 64];
+        let mut new = old;
+        new[3] = 1;
+        new[6] = 2;
+        new[40..48].copy_from_slice(&u64::MAX.to_le_bytes());
+
+        let patches = diff(&old, &new).unwrap();
+        assert_eq!(patches.len(), 2, "Nearby changes should share a patch");
+        assert_eq!(patches[0], StatePatch::new(3, [1, 0, 0, 2]));
+
+        let decoded = decode_patches(&encode_patches(&patches)).unwrap();
+        assert_eq!(decoded, patches);
+
+        let mut data = old;
+        apply_patches(&mut data, &decoded).unwrap();
+        assert_eq!(data, new);
+    }
+
+    #[test]
+    fn rejects_out_of_bounds_patch() {
+        let mut data = [0u8; 16];
+        let patches = [StatePatch::new(0, [1]), StatePatch::new(12, [1; 8])];
+
+        assert!(apply_patches(&mut data, &patches).is_err());
+        assert_eq!(data, [0u8; 16], "Nothing should be written");
+        assert!(decode_patches(&encode_patches(&patches)[..10]).is_err());
+    }
+}
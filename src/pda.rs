@@ -1,7 +1,25 @@
 use sha2::{Digest, Sha256};
 use solana_pubkey::Pubkey;
 
-use crate::constants::PROGRAM_ID;
+use crate::constants::{DELEGATION_PROGRAM_ID, PROGRAM_ID};
+
+/// The seed of the authority account PDA.
+pub const DELEGATION_RECORD: &[u8] = b"delegation";
+
+/// The account to store the delegated account seeds.
+pub const DELEGATION_METADATA: &[u8] = b"delegation-metadata";
+
+/// The seed of the buffer account PDA.
+pub const BUFFER: &[u8] = b"buffer";
+
+/// The seed of the committed state PDA.
+pub const COMMIT_STATE: &[u8] = b"state-diff";
+
+/// The seed of a commit state record PDA.
+pub const COMMIT_RECORD: &[u8] = b"commit-state-record";
+
+/// The seed of a session token PDA.
+pub const SESSION: &[u8] = b"session";
 
 fn compute_seed_hash(chunks: &[&[u8]]) -> [u8; 32] {
     let mut hasher = Sha256::new();
@@ -16,7 +34,12 @@ pub fn world_seed_hash(owner: &Pubkey, name: &str) -> [u8; 32] {
 }
 
 pub fn state_seed_hash(world: &Pubkey, state_name: &str, owner: &Pubkey) -> [u8; 32] {
-    compute_seed_hash(&[b"state", world.as_ref(), state_name.as_bytes(), owner.as_ref()])
+    compute_seed_hash(&[
+        b"state",
+        world.as_ref(),
+        state_name.as_bytes(),
+        owner.as_ref(),
+    ])
 }
 
 pub fn find_world_pda(owner: &Pubkey, name: &str) -> (Pubkey, u8) {
@@ -24,7 +47,51 @@ pub fn find_world_pda(owner: &Pubkey, name: &str) -> (Pubkey, u8) {
     Pubkey::find_program_address(&[seed_hash.as_ref(), owner.as_ref()], &PROGRAM_ID)
 }
 
+/// State accounts used to be derived like worlds, without the world key in the
+/// seed. Kept so deployments from that layout can still be located.
+pub fn find_legacy_state_pda(owner: &Pubkey, state_name: &str) -> (Pubkey, u8) {
+    find_world_pda(owner, state_name)
+}
+
 pub fn find_state_pda(world: &Pubkey, state_name: &str, owner: &Pubkey) -> (Pubkey, u8) {
     let seed_hash = state_seed_hash(world, state_name, owner);
     Pubkey::find_program_address(&[seed_hash.as_ref(), owner.as_ref()], &PROGRAM_ID)
 }
+
+pub fn find_session_pda(world: &Pubkey, owner: &Pubkey, session_key: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            SESSION,
+            world.as_ref(),
+            owner.as_ref(),
+            session_key.as_ref(),
+        ],
+        &PROGRAM_ID,
+    )
+}
+
+pub fn find_buffer_pda(account: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[BUFFER, account.as_ref()], &PROGRAM_ID)
+}
+
+pub fn find_delegation_record_pda(account: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[DELEGATION_RECORD, account.as_ref()],
+        &DELEGATION_PROGRAM_ID,
+    )
+}
+
+pub fn find_delegation_metadata_pda(account: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[DELEGATION_METADATA, account.as_ref()],
+        &DELEGATION_PROGRAM_ID,
+    )
+}
+
+pub fn find_commit_state_pda(account: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[COMMIT_STATE, account.as_ref()], &DELEGATION_PROGRAM_ID)
+}
+
+pub fn find_commit_record_pda(account: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[COMMIT_RECORD, account.as_ref()], &DELEGATION_PROGRAM_ID)
+}
@@ -1,7 +1,6 @@
-use anyhow::{Context, Result};
 use mpl_core::instructions::CreateV1Builder;
 use mpl_core::Asset;
-use solana_client::rpc_client::RpcClient;
+use solana_client::{nonblocking::rpc_client::RpcClient as AsyncRpcClient, rpc_client::RpcClient};
 use solana_instruction::Instruction;
 use solana_pubkey::Pubkey;
 
@@ -14,7 +13,7 @@ pub fn create_mpl_core_asset_ix(
     payer: Pubkey,
     name: &str,
     uri: &str,
-) -> Result<Instruction> {
+) -> Result<Instruction, WorldError> {
     let mut builder = CreateV1Builder::new();
     builder
         .asset(*asset)
@@ -28,37 +27,54 @@ pub fn create_mpl_core_asset_ix(
     Ok(create_ix)
 }
 
-pub fn fetch_mpl_core_asset(rpc: &RpcClient, asset: &Pubkey) -> Result<Asset> {
+pub fn fetch_mpl_core_asset(rpc: &RpcClient, asset: &Pubkey) -> Result<Asset, WorldError> {
     let account_data = rpc
         .get_account_data(asset)
         .map_err(|e| WorldError::AccountNotFound(format!("Failed to fetch account: {}", e)))?;
 
+    decode_mpl_core_asset(asset, &account_data)
+}
+
+pub async fn fetch_mpl_core_asset_async(
+    rpc: &AsyncRpcClient,
+    asset: &Pubkey,
+) -> Result<Asset, WorldError> {
+    let account_data = rpc
+        .get_account_data(asset)
+        .await
+        .map_err(|e| WorldError::AccountNotFound(format!("Failed to fetch account: {}", e)))?;
+
+    decode_mpl_core_asset(asset, &account_data)
+}
+
+fn decode_mpl_core_asset(asset: &Pubkey, account_data: &[u8]) -> Result<Asset, WorldError> {
     if account_data.is_empty() {
-        return Err(
-            WorldError::AccountNotFound(format!("Account {} does not exist", asset)).into(),
-        );
+        return Err(WorldError::AccountNotFound(format!(
+            "Account {} does not exist",
+            asset
+        )));
     }
 
-    let asset = Asset::from_bytes(&account_data)
+    let asset = Asset::from_bytes(account_data)
         .map_err(|e| WorldError::AssetDeserializationError(format!("{}", e)))?;
     Ok(*asset)
 }
 
-pub async fn fetch_metadata_from_uri(metadata_uri: &str) -> Result<Metadata> {
+pub async fn fetch_metadata_from_uri(metadata_uri: &str) -> Result<Metadata, WorldError> {
     let response = reqwest::get(metadata_uri).await.map_err(|e| {
         WorldError::MetadataFetchError(format!("Failed to download metadata: {}", e))
     })?;
 
     if !response.status().is_success() {
-        return Err(
-            WorldError::MetadataFetchError(format!("HTTP error: {}", response.status())).into(),
-        );
+        return Err(WorldError::MetadataFetchError(format!(
+            "HTTP error: {}",
+            response.status()
+        )));
     }
 
-    let metadata: Metadata = response
-        .json()
-        .await
-        .context("Failed to parse metadata JSON")?;
+    let metadata: Metadata = response.json().await.map_err(|e| {
+        WorldError::MetadataFetchError(format!("Failed to parse metadata JSON: {}", e))
+    })?;
 
     Ok(metadata)
 }
@@ -1,37 +1,37 @@
 use crate::error::WorldError;
 use crate::profile::types::ImageSource;
-use anyhow::{ensure, Result};
 
 pub const MAX_IMAGE_SIZE: usize = 10 * 1024 * 1024; // 10 MB
 
-pub async fn load_image_data(source: &ImageSource) -> Result<Vec<u8>> {
+pub async fn load_image_data(source: &ImageSource) -> Result<Vec<u8>, WorldError> {
     match source {
         ImageSource::LocalFile(path) => tokio::fs::read(path)
             .await
-            .map_err(|e| WorldError::ImageReadError(format!("Failed to read file: {}", e)).into()),
+            .map_err(|e| WorldError::ImageReadError(format!("Failed to read file: {}", e))),
         ImageSource::Url(url) => {
             let response = reqwest::get(url).await.map_err(|e| {
                 WorldError::ImageDownloadError(format!("Failed to download: {}", e))
             })?;
 
-            ensure!(
-                response.status().is_success(),
-                WorldError::ImageDownloadError(format!("HTTP error: {}", response.status()))
-            );
+            if !response.status().is_success() {
+                return Err(WorldError::ImageDownloadError(format!(
+                    "HTTP error: {}",
+                    response.status()
+                )));
+            }
 
             response.bytes().await.map(|b| b.to_vec()).map_err(|e| {
-                WorldError::ImageDownloadError(format!("Failed to read response: {}", e)).into()
+                WorldError::ImageDownloadError(format!("Failed to read response: {}", e))
             })
         }
     }
 }
 
-pub fn validate_image(image_data: &[u8]) -> Result<()> {
+pub fn validate_image(image_data: &[u8]) -> Result<(), WorldError> {
     // Check size
-    ensure!(
-        image_data.len() <= MAX_IMAGE_SIZE,
-        WorldError::ImageTooLarge(image_data.len(), MAX_IMAGE_SIZE)
-    );
+    if image_data.len() > MAX_IMAGE_SIZE {
+        return Err(WorldError::ImageTooLarge(image_data.len(), MAX_IMAGE_SIZE));
+    }
 
     // Validate image format by trying to load it
     let _img = image::load_from_memory(image_data).map_err(|e| {
@@ -3,7 +3,10 @@ pub mod image;
 pub mod types;
 pub mod uploader;
 
-pub use asset::{create_mpl_core_asset_ix, fetch_metadata_from_uri, fetch_mpl_core_asset};
+pub use asset::{
+    create_mpl_core_asset_ix, fetch_metadata_from_uri, fetch_mpl_core_asset,
+    fetch_mpl_core_asset_async,
+};
 pub use image::{load_image_data, validate_image, MAX_IMAGE_SIZE};
 pub use types::{ImageSource, Metadata, ProfilePicture, ProfilePictureData};
 pub use uploader::ArweaveUploader;
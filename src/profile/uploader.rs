@@ -1,8 +1,9 @@
-use anyhow::{Context, Result};
 use arweave_rs::Arweave;
 use std::path::PathBuf;
 use url::Url;
 
+use crate::error::WorldError;
+
 pub struct ArweaveUploader {
     wallet_path: Option<String>,
     gateway_url: String,
@@ -16,7 +17,11 @@ impl ArweaveUploader {
         }
     }
 
-    pub async fn upload(&self, data: &[u8], content_type: Option<&str>) -> Result<String> {
+    pub async fn upload(
+        &self,
+        data: &[u8],
+        content_type: Option<&str>,
+    ) -> Result<String, WorldError> {
         use std::io::Write;
         use tempfile::NamedTempFile;
 
@@ -25,19 +30,28 @@ impl ArweaveUploader {
         let wallet_path_buf = PathBuf::from(&wallet_path);
 
         // Parse gateway URL
-        let base_url = Url::parse(&self.gateway_url).context("Invalid Arweave gateway URL")?;
+        let base_url = Url::parse(&self.gateway_url)
+            .map_err(|e| WorldError::UploadError(format!("Invalid Arweave gateway URL: {}", e)))?;
 
         // Initialize Arweave client with wallet
-        let arweave = Arweave::from_keypair_path(wallet_path_buf, base_url)
-            .context("Failed to initialize Arweave client. Ensure wallet file is valid")?;
+        let arweave = Arweave::from_keypair_path(wallet_path_buf, base_url).map_err(|e| {
+            WorldError::UploadError(format!(
+                "Failed to initialize Arweave client. Ensure wallet file is valid: {}",
+                e
+            ))
+        })?;
 
         // Create a temporary file with the data
-        let mut temp_file = NamedTempFile::new().context("Failed to create temporary file")?;
-        temp_file
-            .write_all(data)
-            .context("Failed to write data to temporary file")?;
+        let mut temp_file = NamedTempFile::new().map_err(|e| {
+            WorldError::UploadError(format!("Failed to create temporary file: {}", e))
+        })?;
+        temp_file.write_all(data).map_err(|e| {
+            WorldError::UploadError(format!("Failed to write data to temporary file: {}", e))
+        })?;
         let temp_path = temp_file.path().to_path_buf();
-        temp_file.flush()?;
+        temp_file.flush().map_err(|e| {
+            WorldError::UploadError(format!("Failed to flush temporary file: {}", e))
+        })?;
 
         use arweave_rs::crypto::base64::Base64;
         use arweave_rs::transaction::tags::Tag;
@@ -51,21 +65,22 @@ impl ArweaveUploader {
 
         // Get the fee estimate
         let target = Base64::from("".as_bytes());
-        let fee = arweave
-            .get_fee(target, data.to_vec())
-            .await
-            .context("Failed to get Arweave fee estimate")?;
+        let fee = arweave.get_fee(target, data.to_vec()).await.map_err(|e| {
+            WorldError::UploadError(format!("Failed to get Arweave fee estimate: {}", e))
+        })?;
 
         // Upload file
         let (tx_id, _fee_paid) = arweave
             .upload_file_from_path(temp_path, tags, fee)
             .await
-            .context("Failed to upload file to Arweave")?;
+            .map_err(|e| {
+                WorldError::UploadError(format!("Failed to upload file to Arweave: {}", e))
+            })?;
 
         Ok(tx_id)
     }
 
-    fn get_wallet_path(&self) -> Result<String> {
+    fn get_wallet_path(&self) -> Result<String, WorldError> {
         if let Some(path) = &self.wallet_path {
             if std::path::Path::new(path).exists() {
                 return Ok(path.clone());
@@ -80,14 +95,16 @@ impl ArweaveUploader {
 
         let default_path = dirs::home_dir()
             .map(|h| h.join(".arweave").join("wallet.json"))
-            .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?;
+            .ok_or_else(|| {
+                WorldError::UploadError("Could not determine home directory".to_string())
+            })?;
 
         if default_path.exists() {
             return Ok(default_path.to_string_lossy().to_string());
         }
 
-        Err(anyhow::anyhow!(
-            "Arweave wallet not found. Please provide wallet path or set ARWEAVE_WALLET environment variable"
+        Err(WorldError::UploadError(
+            "Arweave wallet not found. Please provide wallet path or set ARWEAVE_WALLET environment variable".to_string(),
         ))
     }
 
@@ -0,0 +1,223 @@
+use std::time::Duration;
+
+use solana_sdk::{signature::Signature, transaction::TransactionError};
+
+use crate::error::WorldError;
+
+/// How [`WorldClient`](crate::client::WorldClient) retries failed sends.
+/// Only errors [`is_retryable`] accepts are retried; program errors are
+/// returned on the first attempt.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts per send, the first one included. `1` disables retries.
+    pub max_attempts: u32,
+    /// Wait before the second attempt, doubled for every attempt after it.
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Keep resending the signed transaction until it confirms or its
+    /// blockhash expires, instead of sending it once and polling.
+    pub rebroadcast: bool,
+    pub rebroadcast_interval: Duration,
+}
+
+impl RetryPolicy {
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Wait after failed attempt number `attempt`, counting from 1.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+
+    /// What a send loop does with the `result` of attempt number `attempt`
+    /// of the transaction signed as `signature`, which is `resent` when the
+    /// loop already sent it before. Expired transactions are only retried
+    /// when the caller `can_resign` them.
+    pub(crate) fn next_attempt(
+        &self,
+        attempt: u32,
+        result: Result<Signature, WorldError>,
+        signature: Signature,
+        resent: bool,
+        can_resign: bool,
+    ) -> Attempt {
+        let error = match result {
+            Ok(signature) => return Attempt::Done(Ok(signature)),
+            // An earlier send of this same transaction landed.
+            Err(error) if resent && is_already_processed(&error) => {
+                return Attempt::Done(Ok(signature))
+            }
+            Err(error) => error,
+        };
+
+        // On a first send it is some other, identical transaction that
+        // landed, and this write has not been applied: sign it anew.
+        let duplicate = is_already_processed(&error);
+        let resign = duplicate || needs_new_blockhash(&error);
+        if attempt >= self.max_attempts
+            || !(duplicate || is_retryable(&error))
+            || (resign && !can_resign)
+        {
+            return Attempt::Done(Err(error));
+        }
+        Attempt::Retry {
+            backoff: self.backoff(attempt),
+            resign,
+        }
+    }
+}
+
+/// Outcome of [`RetryPolicy::next_attempt`].
+#[derive(Debug)]
+pub(crate) enum Attempt {
+    Done(Result<Signature, WorldError>),
+    /// Wait `backoff`, re-signing first if `resign` is set, then send again.
+    Retry {
+        backoff: Duration,
+        resign: bool,
+    },
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(4),
+            rebroadcast: false,
+            rebroadcast_interval: Duration::from_secs(2),
+        }
+    }
+}
+
+/// Whether `error` is transient: the endpoint was unreachable or unhealthy,
+/// the transaction expired, or it lost a race for account locks. Program and
+/// signing errors would fail the same way again.
+pub fn is_retryable(error: &WorldError) -> bool {
+    match error {
+        WorldError::TransientRpcError(_) | WorldError::TransactionExpired(_) => true,
+        WorldError::TransactionError { error, .. } => matches!(
+            error,
+            TransactionError::BlockhashNotFound
+                | TransactionError::AccountInUse
+                | TransactionError::ClusterMaintenance
+                | TransactionError::WouldExceedMaxBlockCostLimit
+                | TransactionError::WouldExceedMaxAccountCostLimit
+                | TransactionError::WouldExceedMaxVoteCostLimit
+                | TransactionError::WouldExceedAccountDataBlockLimit
+        ),
+        _ => false,
+    }
+}
+
+/// Whether retrying `error` needs the transaction signed over a new blockhash.
+/// Other retries resend the same transaction, so a send that did land
+/// cannot be applied twice.
+pub(crate) fn needs_new_blockhash(error: &WorldError) -> bool {
+    matches!(
+        error,
+        WorldError::TransactionExpired(_)
+            | WorldError::TransactionError {
+                error: TransactionError::BlockhashNotFound,
+                ..
+            }
+    )
+}
+
+/// A transaction the node has already seen comes back as `AlreadyProcessed`:
+/// a resend of one that landed, or an identical transaction signed over the
+/// same blockhash. Only the first is a success; see [`RetryPolicy::next_attempt`].
+pub(crate) fn is_already_processed(error: &WorldError) -> bool {
+    matches!(
+        error,
+        WorldError::TransactionError {
+            error: TransactionError::AlreadyProcessed,
+            ..
+        }
+    )
+}
+
+#[cfg(test)]
+mod test_retry {
+    use super::*;
+    use solana_instruction::error::InstructionError;
+
+    fn already_processed() -> Result<Signature, WorldError> {
+        Err(TransactionError::AlreadyProcessed.into())
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_max() {
+        let policy = RetryPolicy::default();
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(250));
+        assert_eq!(policy.backoff(3), Duration::from_secs(1));
+        assert_eq!(policy.backoff(40), policy.max_backoff);
+    }
+
+    #[test]
+    fn program_errors_are_not_retried() {
+        let program_error: WorldError =
+            TransactionError::InstructionError(0, InstructionError::Custom(1)).into();
+        let expired: WorldError = TransactionError::BlockhashNotFound.into();
+
+        assert!(!is_retryable(&program_error));
+        assert!(is_retryable(&expired));
+        assert!(needs_new_blockhash(&expired));
+        assert!(is_retryable(&WorldError::TransientRpcError(
+            "timed out".to_string()
+        )));
+        assert!(!is_retryable(&WorldError::RpcError(
+            "bad params".to_string()
+        )));
+    }
+
+    #[test]
+    fn next_attempt_follows_policy() {
+        let policy = RetryPolicy::default();
+        let signature = Signature::new_unique();
+        let expired = || Err(TransactionError::BlockhashNotFound.into());
+
+        assert!(matches!(
+            policy.next_attempt(2, already_processed(), signature, true, false),
+            Attempt::Done(Ok(landed)) if landed == signature
+        ));
+        assert!(matches!(
+            policy.next_attempt(1, expired(), signature, false, true),
+            Attempt::Retry { resign: true, backoff } if backoff == policy.backoff(1)
+        ));
+        assert!(matches!(
+            policy.next_attempt(1, expired(), signature, false, false),
+            Attempt::Done(Err(_))
+        ));
+        assert!(matches!(
+            policy.next_attempt(policy.max_attempts, expired(), signature, false, true),
+            Attempt::Done(Err(_))
+        ));
+    }
+
+    #[test]
+    fn first_send_already_processed_is_resigned() {
+        let policy = RetryPolicy::default();
+        let signature = Signature::new_unique();
+
+        // Write A, write B, write A over one blockhash: the second A is
+        // identical to the first and comes back AlreadyProcessed on its own
+        // first send. It must not report the first A's signature.
+        assert!(matches!(
+            policy.next_attempt(1, already_processed(), signature, false, true),
+            Attempt::Retry { resign: true, .. }
+        ));
+        assert!(matches!(
+            policy.next_attempt(1, already_processed(), signature, false, false),
+            Attempt::Done(Err(ref error)) if is_already_processed(error)
+        ));
+    }
+}
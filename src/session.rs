@@ -0,0 +1,195 @@
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use solana_instruction::Instruction;
+use solana_keypair::Keypair;
+use solana_pubkey::Pubkey;
+use solana_signer::Signer;
+
+use crate::{
+    error::WorldError,
+    instructions::{authorize_session_ix, revoke_session_ix},
+    pda::find_session_pda,
+};
+
+/// Sessions this close to expiry are treated as expired, so a write signed
+/// with one does not race the deadline.
+const EXPIRY_MARGIN_SECS: i64 = 30;
+
+/// A short-lived key allowed to write one owner's states in one world, so
+/// real-time writes don't need the owner's wallet to sign each of them.
+pub struct Session {
+    pub keypair: Keypair,
+    pub owner: Pubkey,
+    pub world: Pubkey,
+    /// Unix seconds after which the program rejects the session.
+    pub valid_until: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredSession {
+    keypair: Vec<u8>,
+    owner: String,
+    world: String,
+    valid_until: i64,
+}
+
+impl Session {
+    pub fn generate(owner: Pubkey, world: Pubkey, validity: Duration) -> Self {
+        Self {
+            keypair: Keypair::new(),
+            owner,
+            world,
+            valid_until: unix_now() + validity.as_secs() as i64,
+        }
+    }
+
+    pub fn session_key(&self) -> Pubkey {
+        self.keypair.pubkey()
+    }
+
+    pub fn token_address(&self) -> Pubkey {
+        find_session_pda(&self.world, &self.owner, &self.session_key()).0
+    }
+
+    pub fn is_expired(&self) -> bool {
+        unix_now() + EXPIRY_MARGIN_SECS >= self.valid_until
+    }
+
+    /// The instruction the owner signs to activate this session.
+    pub fn authorize_ix(&self) -> Instruction {
+        authorize_session_ix(self.owner, self.world, self.session_key(), self.valid_until)
+    }
+
+    pub fn revoke_ix(&self) -> Instruction {
+        revoke_session_ix(self.owner, self.world, self.session_key())
+    }
+
+    /// Where sessions are kept unless a directory is given explicitly.
+    pub fn default_dir() -> Result<PathBuf, WorldError> {
+        let dir = dirs::data_local_dir()
+            .ok_or_else(|| WorldError::SessionError("no local data directory".to_string()))?;
+        Ok(dir.join("mojo").join("sessions"))
+    }
+
+    pub fn save(&self) -> Result<PathBuf, WorldError> {
+        self.save_in(&Self::default_dir()?)
+    }
+
+    /// Writes the session to `dir`, readable by the current user only.
+    pub fn save_in(&self, dir: &Path) -> Result<PathBuf, WorldError> {
+        let stored = StoredSession {
+            keypair: self.keypair.to_bytes().to_vec(),
+            owner: self.owner.to_string(),
+            world: self.world.to_string(),
+            valid_until: self.valid_until,
+        };
+        let json = serde_json::to_vec(&stored).map_err(|_| WorldError::SerializationError)?;
+
+        fs::create_dir_all(dir).map_err(session_error)?;
+        let path = session_path(dir, &self.world, &self.owner);
+
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+        let mut file = options.open(&path).map_err(session_error)?;
+        file.write_all(&json).map_err(session_error)?;
+        Ok(path)
+    }
+
+    pub fn load(world: &Pubkey, owner: &Pubkey) -> Result<Option<Self>, WorldError> {
+        Self::load_from(&Self::default_dir()?, world, owner)
+    }
+
+    /// Reads the session saved for `owner` in `world`, if there is one.
+    /// Expired sessions are returned as well; check [`Session::is_expired`].
+    pub fn load_from(
+        dir: &Path,
+        world: &Pubkey,
+        owner: &Pubkey,
+    ) -> Result<Option<Self>, WorldError> {
+        let path = session_path(dir, world, owner);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let json = fs::read(&path).map_err(session_error)?;
+        let stored: StoredSession =
+            serde_json::from_slice(&json).map_err(|_| WorldError::SerializationError)?;
+        let keypair = Keypair::try_from(stored.keypair.as_slice())
+            .map_err(|_| WorldError::SerializationError)?;
+
+        Ok(Some(Self {
+            keypair,
+            owner: stored
+                .owner
+                .parse()
+                .map_err(|_| WorldError::SerializationError)?,
+            world: stored
+                .world
+                .parse()
+                .map_err(|_| WorldError::SerializationError)?,
+            valid_until: stored.valid_until,
+        }))
+    }
+
+    pub fn delete(&self) -> Result<(), WorldError> {
+        self.delete_in(&Self::default_dir()?)
+    }
+
+    pub fn delete_in(&self, dir: &Path) -> Result<(), WorldError> {
+        match fs::remove_file(session_path(dir, &self.world, &self.owner)) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(session_error(err)),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn session_path(dir: &Path, world: &Pubkey, owner: &Pubkey) -> PathBuf {
+    dir.join(format!("{world}-{owner}.json"))
+}
+
+fn session_error(err: std::io::Error) -> WorldError {
+    WorldError::SessionError(err.to_string())
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test_session {
+    use super::*;
+
+    #[test]
+    fn saved_session_loads_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let (owner, world) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let session = Session::generate(owner, world, Duration::from_secs(3600));
+
+        session.save_in(dir.path()).unwrap();
+        let loaded = Session::load_from(dir.path(), &world, &owner)
+            .unwrap()
+            .expect("Session should be saved");
+
+        assert_eq!(loaded.session_key(), session.session_key());
+        assert_eq!(loaded.valid_until, session.valid_until);
+        assert!(!loaded.is_expired());
+        assert!(Session::generate(owner, world, Duration::ZERO).is_expired());
+
+        session.delete_in(dir.path()).unwrap();
+        assert!(Session::load_from(dir.path(), &world, &owner)
+            .unwrap()
+            .is_none());
+    }
+}
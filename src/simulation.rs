@@ -0,0 +1,107 @@
+use solana_client::{
+    rpc_config::RpcSimulateTransactionConfig, rpc_response::RpcSimulateTransactionResult,
+};
+use solana_sdk::{
+    commitment_config::CommitmentConfig, signature::Signature, transaction::TransactionError,
+};
+
+use crate::error::WorldError;
+
+/// What a transaction would have done, as reported by `simulateTransaction`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SimulationReport {
+    /// Signature of the simulated transaction. It was never sent.
+    pub signature: Signature,
+    pub logs: Vec<String>,
+    pub units_consumed: Option<u64>,
+    pub error: Option<TransactionError>,
+    /// The `Custom` code returned by the failing program, if any.
+    pub program_error: Option<u32>,
+}
+
+impl SimulationReport {
+    pub fn new(signature: Signature, result: RpcSimulateTransactionResult) -> Self {
+        let program_error = match result.err.clone().map(WorldError::from) {
+            Some(WorldError::TransactionError { program_error, .. }) => program_error,
+            _ => None,
+        };
+
+        Self {
+            signature,
+            logs: result.logs.unwrap_or_default(),
+            units_consumed: result.units_consumed,
+            error: result.err,
+            program_error,
+        }
+    }
+
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// The error the transaction would have failed with, if any.
+    pub fn result(&self) -> Result<(), WorldError> {
+        match &self.error {
+            Some(error) => Err(error.clone().into()),
+            None => Ok(()),
+        }
+    }
+
+    /// What a dry-run send returns instead of a signature: the error a real
+    /// send would have failed with, or [`WorldError::DryRun`] carrying this
+    /// report when it would have succeeded.
+    pub fn into_dry_run_error(self) -> WorldError {
+        match self.result() {
+            Err(error) => error,
+            Ok(()) => WorldError::DryRun(Box::new(self)),
+        }
+    }
+}
+
+/// Simulations skip signature checks and swap in a current blockhash, so
+/// transactions can be previewed without fetching one first.
+pub(crate) fn simulation_config(commitment: CommitmentConfig) -> RpcSimulateTransactionConfig {
+    RpcSimulateTransactionConfig {
+        sig_verify: false,
+        replace_recent_blockhash: true,
+        commitment: Some(commitment),
+        ..RpcSimulateTransactionConfig::default()
+    }
+}
+
+#[cfg(test)]
+mod test_simulation {
+    use super::*;
+    use solana_instruction::error::InstructionError;
+
+    #[test]
+    fn decodes_program_error() {
+        let result = RpcSimulateTransactionResult {
+            err: Some(TransactionError::InstructionError(
+                1,
+                InstructionError::Custom(6),
+            )),
+            logs: Some(vec!["Program log: bad state".to_string()]),
+            units_consumed: Some(1_400),
+            accounts: None,
+            loaded_accounts_data_size: None,
+            return_data: None,
+            inner_instructions: None,
+            replacement_blockhash: None,
+        };
+
+        let report = SimulationReport::new(Signature::default(), result);
+
+        assert!(!report.succeeded());
+        assert_eq!(report.program_error, Some(6));
+        assert_eq!(report.logs.len(), 1);
+        assert!(report.result().is_err());
+        assert!(matches!(
+            report.into_dry_run_error(),
+            WorldError::TransactionError {
+                program_error: Some(6),
+                ..
+            }
+        ));
+    }
+}
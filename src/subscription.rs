@@ -0,0 +1,190 @@
+use std::time::Duration;
+
+use futures::StreamExt;
+use solana_account_decoder_client_types::UiAccountEncoding;
+use solana_client::{nonblocking::pubsub_client::PubsubClient, rpc_config::RpcAccountInfoConfig};
+use solana_pubkey::Pubkey;
+use solana_sdk::commitment_config::CommitmentConfig;
+use tokio::sync::mpsc::Sender;
+
+use crate::{
+    error::WorldError,
+    world::{decode_state, MojoState},
+};
+
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_millis(500);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// Capacity of the channel handed to [`watch_account`] by
+/// [`World::subscribe_state`](crate::world::World::subscribe_state).
+pub const UPDATE_BUFFER: usize = 16;
+
+/// Streams decoded updates of `address` into `updates` until the receiver is
+/// dropped. Dropped connections are reopened with exponential backoff; each
+/// failed attempt is reported on the channel before retrying.
+///
+/// The websocket is read even while `updates` is full. Until there is room
+/// again only the newest update is kept, so a slow consumer skips stale
+/// states instead of letting them pile up.
+pub async fn watch_account<T: MojoState + Send>(
+    ws_url: String,
+    address: Pubkey,
+    commitment: CommitmentConfig,
+    updates: Sender<Result<T, WorldError>>,
+) {
+    let config = RpcAccountInfoConfig {
+        encoding: Some(UiAccountEncoding::Base64),
+        commitment: Some(commitment),
+        ..RpcAccountInfoConfig::default()
+    };
+    let mut delay = INITIAL_RECONNECT_DELAY;
+
+    while !updates.is_closed() {
+        match PubsubClient::new(&ws_url).await {
+            Ok(client) => match client
+                .account_subscribe(&address, Some(config.clone()))
+                .await
+            {
+                Ok((mut stream, unsubscribe)) => {
+                    delay = INITIAL_RECONNECT_DELAY;
+                    let mut latest = None;
+                    loop {
+                        tokio::select! {
+                            update = stream.next() => match update {
+                                Some(update) => {
+                                    latest = Some(
+                                        update
+                                            .value
+                                            .data
+                                            .decode()
+                                            .ok_or(WorldError::SerializationError)
+                                            .and_then(|data| decode_state(&data)),
+                                    );
+                                }
+                                None => break,
+                            },
+                            permit = updates.reserve(), if latest.is_some() => match (permit, latest.take()) {
+                                (Ok(permit), Some(state)) => permit.send(state),
+                                _ => break,
+                            },
+                            _ = updates.closed() => break,
+                        }
+                    }
+                    drop(stream);
+                    unsubscribe().await;
+
+                    if let Some(state) = latest {
+                        let _ = updates.send(state).await;
+                    }
+                }
+                Err(error) => subscription_failed(&updates, error).await,
+            },
+            Err(error) => subscription_failed(&updates, error).await,
+        }
+
+        if updates.is_closed() {
+            break;
+        }
+        tokio::time::sleep(delay).await;
+        delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+    }
+}
+
+async fn subscription_failed<T>(
+    updates: &Sender<Result<T, WorldError>>,
+    error: impl std::fmt::Display,
+) {
+    let _ = updates
+        .send(Err(WorldError::SubscriptionError(error.to_string())))
+        .await;
+}
+
+#[cfg(test)]
+mod test_subscription {
+    use super::*;
+    use futures::SinkExt;
+    use tokio::{net::TcpListener, sync::mpsc};
+    use tokio_tungstenite::tungstenite::Message;
+
+    /// Accepts two connections, answers each subscription with one update and
+    /// drops the first connection to force a reconnect.
+    async fn serve_two_connections(listener: TcpListener, address: Pubkey) {
+        // base64 of 1u64 and 2u64, little endian.
+        for (subscription, data) in [(7, "AQAAAAAAAAA="), (8, "AgAAAAAAAAA=")] {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(socket).await.unwrap();
+
+            let request = loop {
+                if let Some(Ok(Message::Text(text))) = ws.next().await {
+                    break serde_json::from_str::<serde_json::Value>(&text).unwrap();
+                }
+            };
+            assert_eq!(request["method"], "accountSubscribe");
+            assert_eq!(request["params"][0], address.to_string());
+
+            let response = serde_json::json!({
+                "jsonrpc": "2.0",
+                "result": subscription,
+                "id": request["id"],
+            });
+            let notification = serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": "accountNotification",
+                "params": {
+                    "subscription": subscription,
+                    "result": {
+                        "context": { "slot": 1 },
+                        "value": {
+                            "lamports": 1,
+                            "data": [data, "base64"],
+                            "owner": address.to_string(),
+                            "executable": false,
+                            "rentEpoch": 0,
+                            "space": 8,
+                        },
+                    },
+                },
+            });
+            ws.send(Message::Text(response.to_string())).await.unwrap();
+            ws.send(Message::Text(notification.to_string()))
+                .await
+                .unwrap();
+
+            if subscription == 7 {
+                ws.close(None).await.unwrap();
+            } else {
+                // Keep the second connection open until the client hangs up.
+                while ws.next().await.is_some() {}
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn resubscribes_after_connection_drops() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let ws_url = format!("ws://{}", listener.local_addr().unwrap());
+        let address = Pubkey::new_unique();
+        tokio::spawn(serve_two_connections(listener, address));
+
+        let (updates, mut receiver) = mpsc::channel(UPDATE_BUFFER);
+        tokio::spawn(watch_account::<u64>(
+            ws_url,
+            address,
+            CommitmentConfig::confirmed(),
+            updates,
+        ));
+
+        let mut received = Vec::new();
+        while received.len() < 2 {
+            let update = tokio::time::timeout(Duration::from_secs(10), receiver.recv())
+                .await
+                .expect("Timed out waiting for an update")
+                .expect("Subscription ended early");
+            if let Ok(state) = update {
+                received.push(state);
+            }
+        }
+
+        assert_eq!(received, vec![1, 2], "Update after reconnect missing");
+    }
+}
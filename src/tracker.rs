@@ -0,0 +1,254 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig, signature::Signature, transaction::TransactionError,
+};
+use solana_transaction_status_client_types::TransactionStatus;
+
+use crate::error::WorldError;
+
+/// `getSignatureStatuses` accepts at most this many signatures per request.
+pub const MAX_SIGNATURES_PER_POLL: usize = 256;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SignatureOutcome {
+    Confirmed(Signature),
+    Failed {
+        signature: Signature,
+        error: TransactionError,
+    },
+    /// No status showed up before the tracker's expiry elapsed.
+    Expired(Signature),
+}
+
+/// Follows signatures returned by unconfirmed sends and reports how each one
+/// resolved on a channel, polling their statuses in batches.
+pub struct SignatureTracker {
+    pending: Mutex<HashMap<Signature, Instant>>,
+    outcomes: Sender<SignatureOutcome>,
+    commitment: CommitmentConfig,
+    expiry: Duration,
+}
+
+impl SignatureTracker {
+    pub fn new(
+        commitment: CommitmentConfig,
+        expiry: Duration,
+    ) -> (Self, Receiver<SignatureOutcome>) {
+        let (outcomes, receiver) = mpsc::channel();
+        let tracker = Self {
+            pending: Mutex::new(HashMap::new()),
+            outcomes,
+            commitment,
+            expiry,
+        };
+        (tracker, receiver)
+    }
+
+    pub fn track(&self, signature: Signature) {
+        self.pending
+            .lock()
+            .unwrap()
+            .insert(signature, Instant::now());
+    }
+
+    pub fn pending(&self) -> usize {
+        self.pending.lock().unwrap().len()
+    }
+
+    /// Queries every pending signature once and reports the ones that
+    /// resolved. Returns how many were resolved.
+    pub fn poll(&self, rpc: &RpcClient) -> Result<usize, WorldError> {
+        self.poll_with(|chunk| Ok(rpc.get_signature_statuses(chunk)?.value))
+    }
+
+    /// [`SignatureTracker::poll`] with the status lookup passed in, called
+    /// once per batch of at most [`MAX_SIGNATURES_PER_POLL`] signatures.
+    fn poll_with(
+        &self,
+        mut fetch_statuses: impl FnMut(
+            &[Signature],
+        ) -> Result<Vec<Option<TransactionStatus>>, WorldError>,
+    ) -> Result<usize, WorldError> {
+        let signatures: Vec<Signature> = self.pending.lock().unwrap().keys().copied().collect();
+        let mut resolved = 0;
+
+        for chunk in signatures.chunks(MAX_SIGNATURES_PER_POLL) {
+            let statuses = fetch_statuses(chunk)?;
+
+            let mut pending = self.pending.lock().unwrap();
+            for (signature, status) in chunk.iter().zip(statuses) {
+                let Some(sent_at) = pending.get(signature) else {
+                    continue;
+                };
+                let Some(outcome) = classify(
+                    *signature,
+                    status,
+                    self.commitment,
+                    sent_at.elapsed(),
+                    self.expiry,
+                ) else {
+                    continue;
+                };
+
+                pending.remove(signature);
+                resolved += 1;
+                // The receiver may have been dropped; nobody is listening then.
+                let _ = self.outcomes.send(outcome);
+            }
+        }
+
+        Ok(resolved)
+    }
+
+    /// Polls on a background thread every `interval` until the last other
+    /// handle to the tracker is dropped.
+    pub fn spawn(self: &Arc<Self>, rpc: RpcClient, interval: Duration) -> JoinHandle<()> {
+        let tracker = Arc::clone(self);
+        thread::spawn(move || {
+            while Arc::strong_count(&tracker) > 1 {
+                thread::sleep(interval);
+                if tracker.pending() > 0 {
+                    // Transient RPC failures are retried on the next tick.
+                    let _ = tracker.poll(&rpc);
+                }
+            }
+        })
+    }
+}
+
+/// How `signature`, sent `age` ago, resolved given its `status`, or `None`
+/// while it is still pending.
+fn classify(
+    signature: Signature,
+    status: Option<TransactionStatus>,
+    commitment: CommitmentConfig,
+    age: Duration,
+    expiry: Duration,
+) -> Option<SignatureOutcome> {
+    let confirmed = status
+        .as_ref()
+        .is_some_and(|status| status.satisfies_commitment(commitment));
+
+    match status.and_then(|status| status.err) {
+        Some(error) => Some(SignatureOutcome::Failed { signature, error }),
+        None if confirmed => Some(SignatureOutcome::Confirmed(signature)),
+        None if age >= expiry => Some(SignatureOutcome::Expired(signature)),
+        None => None,
+    }
+}
+
+#[cfg(test)]
+mod test_tracker {
+    use super::*;
+    use solana_transaction_status_client_types::TransactionConfirmationStatus;
+
+    const EXPIRY: Duration = Duration::from_secs(30);
+
+    fn status(
+        confirmation_status: TransactionConfirmationStatus,
+        err: Option<TransactionError>,
+    ) -> Option<TransactionStatus> {
+        Some(TransactionStatus {
+            slot: 1,
+            confirmations: Some(1),
+            status: err.clone().map_or(Ok(()), Err),
+            err,
+            confirmation_status: Some(confirmation_status),
+        })
+    }
+
+    #[test]
+    fn classifies_each_outcome() {
+        let signature = Signature::new_unique();
+        let commitment = CommitmentConfig::confirmed();
+        let classify = |status, age| classify(signature, status, commitment, age, EXPIRY);
+
+        assert_eq!(
+            classify(
+                status(TransactionConfirmationStatus::Confirmed, None),
+                Duration::ZERO
+            ),
+            Some(SignatureOutcome::Confirmed(signature))
+        );
+        assert_eq!(
+            classify(
+                status(
+                    TransactionConfirmationStatus::Processed,
+                    Some(TransactionError::AccountInUse)
+                ),
+                Duration::ZERO
+            ),
+            Some(SignatureOutcome::Failed {
+                signature,
+                error: TransactionError::AccountInUse,
+            })
+        );
+        // Processed but not yet confirmed, or not seen at all, stays pending
+        // until the expiry passes.
+        assert_eq!(
+            classify(
+                status(TransactionConfirmationStatus::Processed, None),
+                Duration::ZERO
+            ),
+            None
+        );
+        assert_eq!(classify(None, EXPIRY - Duration::from_secs(1)), None);
+        assert_eq!(
+            classify(None, EXPIRY),
+            Some(SignatureOutcome::Expired(signature))
+        );
+    }
+
+    #[test]
+    fn polls_in_batches_and_reports_outcomes() {
+        let (tracker, outcomes) = SignatureTracker::new(CommitmentConfig::confirmed(), EXPIRY);
+        let total = MAX_SIGNATURES_PER_POLL + 10;
+        for _ in 0..total {
+            tracker.track(Signature::new_unique());
+        }
+
+        let mut batches = Vec::new();
+        let resolved = tracker
+            .poll_with(|chunk| {
+                batches.push(chunk.len());
+                Ok(chunk
+                    .iter()
+                    .map(|_| status(TransactionConfirmationStatus::Finalized, None))
+                    .collect())
+            })
+            .unwrap();
+
+        assert_eq!(batches, vec![MAX_SIGNATURES_PER_POLL, 10]);
+        assert_eq!(resolved, total);
+        assert_eq!(tracker.pending(), 0);
+        assert_eq!(outcomes.try_iter().count(), total);
+    }
+
+    #[test]
+    fn expires_unseen_signatures_by_age() {
+        let (tracker, outcomes) =
+            SignatureTracker::new(CommitmentConfig::confirmed(), Duration::ZERO);
+        let signature = Signature::new_unique();
+        tracker.track(signature);
+
+        let resolved = tracker
+            .poll_with(|chunk| Ok(vec![None; chunk.len()]))
+            .unwrap();
+
+        assert_eq!(resolved, 1);
+        assert_eq!(
+            outcomes.try_recv(),
+            Ok(SignatureOutcome::Expired(signature))
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use solana_pubkey::Pubkey;
+
+use crate::{
+    client::{
+        RpcType, ER_LAYER_RPC_DEVNET, ER_LAYER_RPC_DEVNET_ASIA, ER_LAYER_RPC_DEVNET_US,
+        ER_LAYER_RPC_LOCALNET, ER_LAYER_RPC_MAINNET, ER_LAYER_RPC_MAINNET_ASIA,
+        ER_LAYER_RPC_MAINNET_US,
+    },
+    instructions::{ASIA_VALIDATOR, EU_VALIDATOR, LOCAL_VALIDATOR, US_VALIDATOR},
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Region {
+    #[default]
+    Europe,
+    UnitedStates,
+    Asia,
+    Local,
+}
+
+/// An ephemeral rollup validator: the identity state accounts are delegated
+/// to, paired with the ER endpoint that serves them.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Validator {
+    pub identity: Pubkey,
+    pub region: Region,
+    /// ER RPC endpoint, overriding the one `region` maps to.
+    pub rpc_url: Option<String>,
+    /// ER websocket endpoint, overriding the one derived from the RPC endpoint.
+    pub ws_url: Option<String>,
+}
+
+impl Validator {
+    pub const EU: Validator = Validator::new(EU_VALIDATOR, Region::Europe);
+    pub const US: Validator = Validator::new(US_VALIDATOR, Region::UnitedStates);
+    pub const ASIA: Validator = Validator::new(ASIA_VALIDATOR, Region::Asia);
+    pub const LOCAL: Validator = Validator::new(LOCAL_VALIDATOR, Region::Local);
+
+    pub const fn new(identity: Pubkey, region: Region) -> Self {
+        Self {
+            identity,
+            region,
+            rpc_url: None,
+            ws_url: None,
+        }
+    }
+
+    /// A validator outside the known regions, reached at `rpc_url`.
+    pub fn custom(identity: Pubkey, rpc_url: impl Into<String>) -> Self {
+        Self::new(identity, Region::default()).with_rpc_url(rpc_url)
+    }
+
+    pub fn with_rpc_url(mut self, rpc_url: impl Into<String>) -> Self {
+        self.rpc_url = Some(rpc_url.into());
+        self
+    }
+
+    pub fn with_ws_url(mut self, ws_url: impl Into<String>) -> Self {
+        self.ws_url = Some(ws_url.into());
+        self
+    }
+
+    pub fn from_region(region: Region) -> Self {
+        match region {
+            Region::Europe => Self::EU,
+            Region::UnitedStates => Self::US,
+            Region::Asia => Self::ASIA,
+            Region::Local => Self::LOCAL,
+        }
+    }
+
+    /// The known validator with the given identity, if any.
+    pub fn from_identity(identity: &Pubkey) -> Option<Self> {
+        [Self::EU, Self::US, Self::ASIA, Self::LOCAL]
+            .into_iter()
+            .find(|validator| validator.identity == *identity)
+    }
+
+    /// The validator a cluster delegates to unless told otherwise.
+    pub fn default_for(cluster: &RpcType) -> Self {
+        match cluster {
+            RpcType::Localnet => Self::LOCAL,
+            _ => Self::default(),
+        }
+    }
+
+    pub fn er_url<'a>(&'a self, cluster: &'a RpcType) -> &'a str {
+        if let Some(rpc_url) = &self.rpc_url {
+            return rpc_url;
+        }
+
+        match (cluster, self.region) {
+            (RpcType::Custom { er_url, .. }, _) => er_url,
+            (RpcType::Localnet, _) | (_, Region::Local) => ER_LAYER_RPC_LOCALNET,
+            (RpcType::Devnet, Region::Europe) => ER_LAYER_RPC_DEVNET,
+            (RpcType::Devnet, Region::UnitedStates) => ER_LAYER_RPC_DEVNET_US,
+            (RpcType::Devnet, Region::Asia) => ER_LAYER_RPC_DEVNET_ASIA,
+            (RpcType::Mainnet, Region::Europe) => ER_LAYER_RPC_MAINNET,
+            (RpcType::Mainnet, Region::UnitedStates) => ER_LAYER_RPC_MAINNET_US,
+            (RpcType::Mainnet, Region::Asia) => ER_LAYER_RPC_MAINNET_ASIA,
+        }
+    }
+}
+
+impl Default for Validator {
+    fn default() -> Self {
+        Self::EU
+    }
+}
+
+impl From<Region> for Validator {
+    fn from(region: Region) -> Self {
+        Self::from_region(region)
+    }
+}
+
+#[cfg(test)]
+mod test_validator {
+    use super::*;
+
+    #[test]
+    fn custom_endpoint_overrides_region() {
+        let validator = Validator::custom(Pubkey::new_unique(), "https://er.example.com");
+
+        assert_eq!(validator.er_url(&RpcType::Devnet), "https://er.example.com");
+        assert_eq!(
+            Validator::US.er_url(&RpcType::Devnet),
+            ER_LAYER_RPC_DEVNET_US
+        );
+    }
+}
@@ -1,24 +1,52 @@
-use anyhow::{ensure, Result};
-use bytemuck::{bytes_of, from_bytes, Pod, Zeroable};
-use solana_client::rpc_client::RpcClient;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use bytemuck::{bytes_of, pod_read_unaligned, Pod, Zeroable};
+use futures::stream::{self, BoxStream, StreamExt};
+use solana_instruction::Instruction;
 use solana_keypair::Keypair;
+use solana_message::AddressLookupTableAccount;
 use solana_pubkey::Pubkey;
-use solana_sdk::signature::Signature;
+use solana_sdk::{compute_budget::ComputeBudgetInstruction, signature::Signature};
 use solana_signer::Signer;
+use tokio::sync::mpsc;
 
 use crate::{
     client::{
-        RpcLayer, RpcType, WorldClient, BASE_LAYER_RPC_DEVNET, BASE_LAYER_RPC_MAINNET,
-        ER_LAYER_RPC_DEVNET, ER_LAYER_RPC_MAINNET,
+        fits_in_transaction, pack_instructions, transaction_size, AsyncWorldClient, RpcLayer,
+        RpcType, WorldClient, WorldClientConfig, MAX_TRANSACTION_SIZE,
+    },
+    constants::{DELEGATION_PROGRAM_ID, PROGRAM_ID},
+    delegation::{
+        fetch_delegation_status, fetch_delegation_status_async, fetch_delegation_statuses,
+        DelegationStatus,
+    },
+    error::WorldError,
+    instructions::{
+        commit_state_ix, create_world_ix, delegate_account_ix, patch_state_ix,
+        patch_with_session_ix, undelegate_account_ix, write_to_world_ix, write_with_session_ix,
+    },
+    offline::UnsignedTransaction,
+    patch::{apply_patches, diff_states, encode_patches, StatePatch},
+    pda::{
+        find_legacy_state_pda, find_state_pda, find_world_pda, state_seed_hash, world_seed_hash,
     },
-    instructions::{create_world_ix, delegate_account_ix, write_to_world_ix},
-    pda::{find_world_pda, world_seed_hash},
     profile::{
-        create_mpl_core_asset_ix, fetch_metadata_from_uri, fetch_mpl_core_asset, load_image_data,
-        validate_image, ArweaveUploader, ImageSource, ProfilePicture, ProfilePictureData,
+        create_mpl_core_asset_ix, fetch_metadata_from_uri, fetch_mpl_core_asset_async,
+        load_image_data, validate_image, ArweaveUploader, ImageSource, ProfilePicture,
+        ProfilePictureData,
     },
+    session::Session,
+    subscription::{watch_account, UPDATE_BUFFER},
+    validator::Validator,
 };
 
+/// Compute budget requested when account creation and delegation share a transaction.
+pub const CREATE_AND_DELEGATE_COMPUTE_UNITS: u32 = 400_000;
+
 pub trait MojoState: Pod + Zeroable + Copy {}
 
 impl<T> MojoState for T where T: Pod + Zeroable + Copy {}
@@ -39,30 +67,369 @@ pub struct WorldData {
     pub world_address: [u8; 32],
 }
 
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReadSource {
+    /// Whichever layer currently holds the authoritative copy.
+    Auto,
+    BaseLayer,
+    #[default]
+    Ephemeral,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StateCopies<T> {
+    pub base: Option<T>,
+    pub ephemeral: Option<T>,
+}
+
+/// One entry of a [`World::write_states`] batch.
+#[derive(Clone, Copy, Debug)]
+pub struct StateWrite<'a> {
+    pub name: &'a str,
+    pub data: &'a [u8],
+}
+
+impl<'a> StateWrite<'a> {
+    pub fn new<T: MojoState>(name: &'a str, state: &'a T) -> Self {
+        Self {
+            name,
+            data: bytes_of(state),
+        }
+    }
+}
+
+/// One transaction sent by [`World::write_states`].
+#[derive(Debug)]
+pub struct WriteBatch {
+    /// Indices of the writes this transaction carried.
+    pub writes: Vec<usize>,
+    pub result: Result<Signature, WorldError>,
+}
+
+/// ER clients per validator and the validator each state is delegated to,
+/// filled in as states are used.
+#[derive(Default)]
+struct Routes {
+    validators: HashMap<Pubkey, Validator>,
+    clients: HashMap<Pubkey, (Arc<WorldClient>, Arc<AsyncWorldClient>)>,
+}
+
 pub struct World {
     pub data: WorldData,
-    pub network: RpcType,
+    network: RpcType,
+    validator: Validator,
+    client: Arc<WorldClient>,
+    async_client: Arc<AsyncWorldClient>,
+    /// Validators besides the built-in ones that states may be delegated to.
+    known_validators: Vec<Validator>,
+    routes: Mutex<Routes>,
+    /// Session keys by owner, used to sign that owner's ER writes.
+    sessions: HashMap<Pubkey, Session>,
 }
 
 impl World {
-    pub fn create_world(network: RpcType, payer: &impl Signer, name: &str) -> Result<Self> {
+    pub fn create_world(
+        network: RpcType,
+        payer: &impl Signer,
+        name: &str,
+    ) -> Result<Self, WorldError> {
+        Self::create_world_with_client(WorldClient::new(&network), payer, name)
+    }
+
+    pub fn create_world_with_client(
+        client: WorldClient,
+        payer: &impl Signer,
+        name: &str,
+    ) -> Result<Self, WorldError> {
+        let (world_data, ix) = Self::create_world_parts(&payer.pubkey(), name);
+        let world = Self::from_client(world_data, client);
+
+        world
+            .client
+            .send_ixs(payer, vec![ix], RpcLayer::BaseLayer)?;
+        Ok(world)
+    }
+
+    pub async fn create_world_async(
+        network: RpcType,
+        payer: &(impl Signer + Sync),
+        name: &str,
+    ) -> Result<Self, WorldError> {
+        let (world_data, ix) = Self::create_world_parts(&payer.pubkey(), name);
+        let world = Self::from_client(world_data, WorldClient::new(&network));
+
+        world
+            .async_client
+            .send_ixs(payer, vec![ix], RpcLayer::BaseLayer)
+            .await?;
+        Ok(world)
+    }
+
+    /// Builds the create transaction for an external signer instead of
+    /// sending it. The returned world is usable once the transaction lands.
+    pub fn build_create_world(
+        network: RpcType,
+        payer: &Pubkey,
+        name: &str,
+    ) -> Result<(Self, UnsignedTransaction), WorldError> {
+        let (world_data, ix) = Self::create_world_parts(payer, name);
+        let world = Self::from_client(world_data, WorldClient::new(&network));
+
+        let tx = world
+            .client
+            .build_unsigned(payer, vec![ix], RpcLayer::BaseLayer)?;
+        Ok((world, tx))
+    }
+
+    /// Sends a transaction signed outside the SDK, e.g. one of the `build_*`
+    /// transactions, on the layer and validator it was built for.
+    pub fn submit_signed(
+        &self,
+        transaction: &UnsignedTransaction,
+    ) -> Result<Signature, WorldError> {
+        let (client, _) = self.clients_for(&transaction.validator);
+        client.submit_transaction(&transaction.transaction, transaction.layer)
+    }
+
+    /// Loads the world if it already exists on chain and creates it otherwise,
+    /// so restarts can call this unconditionally.
+    pub fn create_or_load(
+        network: RpcType,
+        payer: &impl Signer,
+        name: &str,
+    ) -> Result<Self, WorldError> {
+        Self::create_or_load_with_client(WorldClient::new(&network), payer, name)
+    }
+
+    pub fn create_or_load_with_client(
+        client: WorldClient,
+        payer: &impl Signer,
+        name: &str,
+    ) -> Result<Self, WorldError> {
         let (world_pda, _) = find_world_pda(&payer.pubkey(), name);
-        let seed_hash = world_seed_hash(&payer.pubkey(), name);
+
+        match client.get_account(&world_pda, RpcLayer::BaseLayer)? {
+            Some(_) => Self::from_address_with_client(client, &world_pda),
+            None => Self::create_world_with_client(client, payer, name),
+        }
+    }
+
+    /// Loads a world created earlier by `owner` under `name`.
+    pub fn load(network: RpcType, owner: &Pubkey, name: &str) -> Result<Self, WorldError> {
+        let (world_pda, _) = find_world_pda(owner, name);
+        Self::from_address_with_client(WorldClient::new(&network), &world_pda)
+    }
+
+    pub fn from_address(network: RpcType, address: &Pubkey) -> Result<Self, WorldError> {
+        Self::from_address_with_client(WorldClient::new(&network), address)
+    }
+
+    /// Fetches the world account at `address` and checks that it is the PDA its
+    /// stored creator and seed derive to before handing it out.
+    pub fn from_address_with_client(
+        client: WorldClient,
+        address: &Pubkey,
+    ) -> Result<Self, WorldError> {
+        let account = client
+            .get_account(address, RpcLayer::BaseLayer)?
+            .ok_or_else(|| WorldError::AccountNotFound(address.to_string()))?;
+
+        let world_data = decode_state::<WorldData>(&account.data)?;
+        verify_world_data(address, &world_data)?;
+
+        Ok(Self::from_client(world_data, client))
+    }
+
+    fn create_world_parts(payer: &Pubkey, name: &str) -> (WorldData, Instruction) {
+        let (world_pda, _) = find_world_pda(payer, name);
+        let seed_hash = world_seed_hash(payer, name);
 
         let world_data = WorldData {
-            creator: payer.pubkey().to_bytes(),
+            creator: payer.to_bytes(),
             seed: seed_hash,
             world_address: world_pda.to_bytes(),
         };
 
-        let ix = create_world_ix(payer.pubkey(), world_pda, seed_hash, bytes_of(&world_data));
+        let ix = create_world_ix(*payer, world_pda, seed_hash, bytes_of(&world_data));
+        (world_data, ix)
+    }
+
+    fn from_client(data: WorldData, client: WorldClient) -> Self {
+        let async_client = AsyncWorldClient::with_config(
+            client.cluster(),
+            client.validator().clone(),
+            client.config.clone(),
+        );
 
-        WorldClient::new(&network).send_ixs(payer, vec![ix], RpcLayer::BaseLayer)?;
+        Self {
+            data,
+            network: client.cluster().clone(),
+            validator: client.validator().clone(),
+            client: Arc::new(client),
+            async_client: Arc::new(async_client),
+            known_validators: Vec::new(),
+            routes: Mutex::new(Routes::default()),
+            sessions: HashMap::new(),
+        }
+    }
 
-        Ok(Self {
-            data: world_data,
-            network,
-        })
+    /// Delegates new states to `validator`. ER reads and writes of a state
+    /// go to whichever validator it is actually delegated to.
+    pub fn with_validator(mut self, validator: Validator) -> Self {
+        self.validator = validator;
+        self.rebuild_clients(self.client.config.clone());
+        self
+    }
+
+    /// Lets ER routing recognise states delegated to `validator`, e.g. a
+    /// custom validator whose endpoint cannot be told from its identity.
+    pub fn with_known_validator(mut self, validator: Validator) -> Self {
+        self.known_validators.push(validator);
+        self
+    }
+
+    pub fn with_client_config(mut self, config: WorldClientConfig) -> Self {
+        self.rebuild_clients(config);
+        self
+    }
+
+    /// Sends base-layer transactions of this world as v0 transactions using
+    /// `lookup_tables`, e.g. one made by
+    /// [`create_mojo_lookup_table`](crate::lookup_table::create_mojo_lookup_table).
+    pub fn with_lookup_tables(self, lookup_tables: Vec<AddressLookupTableAccount>) -> Self {
+        let config = WorldClientConfig {
+            lookup_tables,
+            ..self.client.config.clone()
+        };
+        self.with_client_config(config)
+    }
+
+    /// Switches every operation of this world to simulation; see
+    /// [`WorldClientConfig::dry_run`].
+    pub fn with_dry_run(self, dry_run: bool) -> Self {
+        let config = WorldClientConfig {
+            dry_run,
+            ..self.client.config.clone()
+        };
+        self.with_client_config(config)
+    }
+
+    /// Signs ER writes of `session.owner` with the session key from now on.
+    /// Fails if the session was made for another world.
+    pub fn with_session(mut self, session: Session) -> Result<Self, WorldError> {
+        self.check_session(&session, &session.owner)?;
+        self.sessions.insert(session.owner, session);
+        Ok(self)
+    }
+
+    /// The unexpired session writes of `owner` are signed with, if any.
+    pub fn session(&self, owner: &Pubkey) -> Option<&Session> {
+        self.sessions
+            .get(owner)
+            .filter(|session| !session.is_expired())
+    }
+
+    /// Authorizes a fresh session key to write `owner`'s states in this world
+    /// for `validity`, saves it locally and uses it for later writes.
+    pub fn create_session(
+        &mut self,
+        owner: &impl Signer,
+        validity: Duration,
+    ) -> Result<Signature, WorldError> {
+        let session = Session::generate(owner.pubkey(), self.world_address(), validity);
+
+        let tx = self
+            .client
+            .send_ixs(owner, vec![session.authorize_ix()], RpcLayer::BaseLayer)?;
+        session.save()?;
+
+        self.sessions.insert(session.owner, session);
+        Ok(tx)
+    }
+
+    /// Picks up the session saved by an earlier [`World::create_session`].
+    /// Expired sessions are deleted instead. Returns whether one was loaded.
+    pub fn load_session(&mut self, owner: &Pubkey) -> Result<bool, WorldError> {
+        let Some(session) = Session::load(&self.world_address(), owner)? else {
+            return Ok(false);
+        };
+
+        self.check_session(&session, owner)?;
+        if session.is_expired() {
+            session.delete()?;
+            return Ok(false);
+        }
+
+        self.sessions.insert(session.owner, session);
+        Ok(true)
+    }
+
+    /// Rejects a session of another world or owner; every write signed with
+    /// it would fail on chain.
+    fn check_session(&self, session: &Session, owner: &Pubkey) -> Result<(), WorldError> {
+        let world = self.world_address();
+        if session.world != world {
+            return Err(WorldError::SessionError(format!(
+                "session is for world {}, not {world}",
+                session.world
+            )));
+        }
+        if session.owner != *owner {
+            return Err(WorldError::SessionError(format!(
+                "session belongs to {}, not {owner}",
+                session.owner
+            )));
+        }
+        Ok(())
+    }
+
+    /// Revokes the session of `owner` on chain and forgets it locally.
+    /// Returns `None` when there was no session to revoke.
+    pub fn revoke_session(&mut self, owner: &impl Signer) -> Result<Option<Signature>, WorldError> {
+        let Some(session) = self.sessions.get(&owner.pubkey()) else {
+            return Ok(None);
+        };
+
+        // Keep the session until the revoke lands, so a failed send can be retried.
+        let tx = self
+            .client
+            .send_ixs(owner, vec![session.revoke_ix()], RpcLayer::BaseLayer)?;
+        if let Some(session) = self.sessions.remove(&owner.pubkey()) {
+            session.delete()?;
+        }
+        Ok(Some(tx))
+    }
+
+    fn rebuild_clients(&mut self, config: WorldClientConfig) {
+        self.client = Arc::new(WorldClient::with_config(
+            &self.network,
+            self.validator.clone(),
+            config.clone(),
+        ));
+        self.async_client = Arc::new(AsyncWorldClient::with_config(
+            &self.network,
+            self.validator.clone(),
+            config,
+        ));
+        self.routes.get_mut().unwrap().clients.clear();
+    }
+
+    pub fn network(&self) -> &RpcType {
+        &self.network
+    }
+
+    /// The validator new states are delegated to; change it with
+    /// [`World::with_validator`], which also rebuilds the clients.
+    pub fn validator(&self) -> &Validator {
+        &self.validator
+    }
+
+    pub fn client(&self) -> &WorldClient {
+        &self.client
+    }
+
+    pub fn async_client(&self) -> &AsyncWorldClient {
+        &self.async_client
     }
 
     pub fn create_state<T: MojoState>(
@@ -70,26 +437,197 @@ impl World {
         payer: &impl Signer,
         name: &str,
         initial_state: &T,
-    ) -> Result<Pubkey> {
-        let (state_pda, _) = find_world_pda(&payer.pubkey(), name);
-        let seed_hash = world_seed_hash(&payer.pubkey(), name);
-        let ix = create_world_ix(
+    ) -> Result<Pubkey, WorldError> {
+        let (state_pda, create_ix, delegate_ix) =
+            self.create_state_ixs(&payer.pubkey(), name, initial_state);
+
+        for ixs in self.create_state_steps(&payer.pubkey(), create_ix, delegate_ix) {
+            self.client.send_ixs(payer, ixs, RpcLayer::BaseLayer)?;
+        }
+        self.remember_route(state_pda, self.validator.clone());
+        Ok(state_pda)
+    }
+
+    /// Creates and delegates the state in a single transaction so it is never
+    /// left created but undelegated. Falls back to the two-transaction
+    /// [`World::create_state`] flow when `T` is too large to fit both instructions.
+    pub fn create_state_atomic<T: MojoState>(
+        &self,
+        payer: &impl Signer,
+        name: &str,
+        initial_state: &T,
+    ) -> Result<Pubkey, WorldError> {
+        let (state_pda, create_ix, delegate_ix) =
+            self.create_state_ixs(&payer.pubkey(), name, initial_state);
+
+        let ixs = create_and_delegate_ixs(create_ix, delegate_ix);
+        if !self
+            .client
+            .fits_in_transaction(&payer.pubkey(), &ixs, RpcLayer::BaseLayer)
+        {
+            return self.create_state(payer, name, initial_state);
+        }
+
+        self.client.send_ixs(payer, ixs, RpcLayer::BaseLayer)?;
+        self.remember_route(state_pda, self.validator.clone());
+        Ok(state_pda)
+    }
+
+    /// Offline counterpart of [`World::create_state_atomic`]. Returns one
+    /// transaction when create and delegate fit together and two otherwise,
+    /// to be submitted in order.
+    pub fn build_create_state<T: MojoState>(
+        &self,
+        payer: &Pubkey,
+        name: &str,
+        initial_state: &T,
+    ) -> Result<(Pubkey, Vec<UnsignedTransaction>), WorldError> {
+        let (state_pda, create_ix, delegate_ix) = self.create_state_ixs(payer, name, initial_state);
+
+        let ixs = create_and_delegate_ixs(create_ix.clone(), delegate_ix.clone());
+        // Built transactions are always legacy, so lookup tables do not count.
+        let with_budget = self
+            .client
+            .reserve_compute_budget(&ixs, RpcLayer::BaseLayer);
+        let txs = if fits_in_transaction(payer, &with_budget) {
+            vec![self
+                .client
+                .build_unsigned(payer, ixs, RpcLayer::BaseLayer)?]
+        } else {
+            vec![
+                self.client
+                    .build_unsigned(payer, vec![create_ix], RpcLayer::BaseLayer)?,
+                self.client
+                    .build_unsigned(payer, vec![delegate_ix], RpcLayer::BaseLayer)?,
+            ]
+        };
+        Ok((state_pda, txs))
+    }
+
+    /// Idempotent [`World::create_state`]: creates the account only if it is
+    /// missing and delegates it only if it is not delegated yet, which also
+    /// finishes a create that stopped before its delegate transaction landed.
+    pub fn create_or_load_state<T: MojoState>(
+        &self,
+        payer: &impl Signer,
+        name: &str,
+        initial_state: &T,
+    ) -> Result<Pubkey, WorldError> {
+        let (state_pda, _) = self.state_address(&payer.pubkey(), name);
+
+        match self.delegate_state::<T>(payer, name) {
+            Ok(_) | Err(WorldError::AlreadyDelegated(_)) => Ok(state_pda),
+            Err(WorldError::AccountNotFound(_)) => self.create_state(payer, name, initial_state),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Delegates a state that exists on the base layer but is not delegated,
+    /// e.g. one handed back by [`World::undelegate_state`].
+    pub fn delegate_state<T: MojoState>(
+        &self,
+        payer: &impl Signer,
+        name: &str,
+    ) -> Result<Signature, WorldError> {
+        let (state_pda, seed_hash) = self.state_address(&payer.pubkey(), name);
+
+        let account = self
+            .client
+            .get_account(&state_pda, RpcLayer::BaseLayer)?
+            .ok_or_else(|| WorldError::AccountNotFound(state_pda.to_string()))?;
+
+        if account.owner == DELEGATION_PROGRAM_ID {
+            return Err(WorldError::AlreadyDelegated(state_pda));
+        }
+        if account.owner != PROGRAM_ID {
+            return Err(WorldError::UnexpectedOwner {
+                account: state_pda,
+                owner: account.owner,
+            });
+        }
+
+        let required_len = core::mem::size_of::<T>();
+        if account.data.len() < required_len {
+            return Err(WorldError::AccountSizeMismatch {
+                expected: required_len,
+                actual: account.data.len(),
+            });
+        }
+
+        // Delegate what is already stored, so a resumed create never
+        // clobbers data written in between.
+        let delegate_ix = delegate_account_ix(
             payer.pubkey(),
             state_pda,
             seed_hash,
-            bytes_of(initial_state),
+            &account.data[..required_len],
+            self.validator.identity,
         );
+        let tx = self
+            .client
+            .send_ixs(payer, vec![delegate_ix], RpcLayer::BaseLayer)?;
+        self.remember_route(state_pda, self.validator.clone());
+        Ok(tx)
+    }
+
+    pub async fn create_state_async<T: MojoState>(
+        &self,
+        payer: &(impl Signer + Sync),
+        name: &str,
+        initial_state: &T,
+    ) -> Result<Pubkey, WorldError> {
+        let (state_pda, create_ix, delegate_ix) =
+            self.create_state_ixs(&payer.pubkey(), name, initial_state);
+
+        for ixs in self.create_state_steps(&payer.pubkey(), create_ix, delegate_ix) {
+            self.async_client
+                .send_ixs(payer, ixs, RpcLayer::BaseLayer)
+                .await?;
+        }
+        self.remember_route(state_pda, self.validator.clone());
+        Ok(state_pda)
+    }
+
+    /// The transactions [`World::create_state`] sends, in order. Delegating
+    /// needs the account the create step makes, so a dry run simulates both
+    /// steps as one transaction where they fit; otherwise only the create
+    /// step is simulated and the dry-run result stops there.
+    fn create_state_steps(
+        &self,
+        payer: &Pubkey,
+        create_ix: Instruction,
+        delegate_ix: Instruction,
+    ) -> Vec<Vec<Instruction>> {
+        if self.client.config.dry_run {
+            let ixs = create_and_delegate_ixs(create_ix.clone(), delegate_ix.clone());
+            if self
+                .client
+                .fits_in_transaction(payer, &ixs, RpcLayer::BaseLayer)
+            {
+                return vec![ixs];
+            }
+        }
+        vec![vec![create_ix], vec![delegate_ix]]
+    }
+
+    fn create_state_ixs<T: MojoState>(
+        &self,
+        payer: &Pubkey,
+        name: &str,
+        initial_state: &T,
+    ) -> (Pubkey, Instruction, Instruction) {
+        let (state_pda, seed_hash) = self.state_address(payer, name);
+        let create_ix = create_world_ix(*payer, state_pda, seed_hash, bytes_of(initial_state));
 
         let delegate_ix = delegate_account_ix(
-            payer.pubkey(),
+            *payer,
             state_pda,
             seed_hash,
             bytes_of(initial_state),
+            self.validator.identity,
         );
 
-        WorldClient::new(&self.network).send_ixs(payer, vec![ix], RpcLayer::BaseLayer)?;
-        WorldClient::new(&self.network).send_ixs(payer, vec![delegate_ix], RpcLayer::BaseLayer)?;
-        Ok(state_pda)
+        (state_pda, create_ix, delegate_ix)
     }
 
     pub fn write_state<T: MojoState>(
@@ -97,60 +635,785 @@ impl World {
         payer: &impl Signer,
         name: &str,
         new_state: &T,
-    ) -> Result<Signature> {
-        let (world_pda, _) = find_world_pda(&payer.pubkey(), name);
-        let seed_hash = world_seed_hash(&payer.pubkey(), name);
-        let ix = write_to_world_ix(payer.pubkey(), world_pda, seed_hash, bytes_of(new_state));
+    ) -> Result<Signature, WorldError> {
+        self.write_state_signed(&payer.pubkey(), Some(payer), name, new_state)
+    }
 
-        let tx = WorldClient::new(&self.network).send_ixs(payer, vec![ix], RpcLayer::Ephemeral)?;
-        Ok(tx)
+    /// [`World::write_state`] for callers that hold `owner`'s session but not
+    /// their keypair. Fails if `owner` has no active session.
+    pub fn write_state_as<T: MojoState>(
+        &self,
+        owner: &Pubkey,
+        name: &str,
+        new_state: &T,
+    ) -> Result<Signature, WorldError> {
+        self.write_state_signed(owner, None, name, new_state)
     }
 
-    pub fn read_state<T: MojoState>(&self, owner: &Pubkey, name: &str) -> Result<T> {
-        let (world_pda, _) = find_world_pda(owner, name);
+    fn write_state_signed<T: MojoState>(
+        &self,
+        owner: &Pubkey,
+        owner_signer: Option<&dyn Signer>,
+        name: &str,
+        new_state: &T,
+    ) -> Result<Signature, WorldError> {
+        let client = self.state_client(owner, name)?;
+        let (ix, session) = self.session_write_ix(owner, name, bytes_of(new_state));
+        let signer = write_signer(owner, session.map(|key| key as _), owner_signer)?;
+
+        client.send_ixs(&signer, vec![ix], RpcLayer::Ephemeral)
+    }
+
+    /// Fire-and-forget variant of [`World::write_state`] for per-tick ER updates.
+    /// The returned signature is not confirmed; hand it to a
+    /// [`SignatureTracker`](crate::tracker::SignatureTracker) to hear about failures.
+    pub fn write_state_unconfirmed<T: MojoState>(
+        &self,
+        payer: &impl Signer,
+        name: &str,
+        new_state: &T,
+    ) -> Result<Signature, WorldError> {
+        self.write_state_unconfirmed_signed(&payer.pubkey(), Some(payer), name, new_state)
+    }
+
+    /// [`World::write_state_unconfirmed`] signed with `owner`'s session key.
+    pub fn write_state_unconfirmed_as<T: MojoState>(
+        &self,
+        owner: &Pubkey,
+        name: &str,
+        new_state: &T,
+    ) -> Result<Signature, WorldError> {
+        self.write_state_unconfirmed_signed(owner, None, name, new_state)
+    }
+
+    fn write_state_unconfirmed_signed<T: MojoState>(
+        &self,
+        owner: &Pubkey,
+        owner_signer: Option<&dyn Signer>,
+        name: &str,
+        new_state: &T,
+    ) -> Result<Signature, WorldError> {
+        let client = self.state_client(owner, name)?;
+        let (ix, session) = self.session_write_ix(owner, name, bytes_of(new_state));
+        let signer = write_signer(owner, session.map(|key| key as _), owner_signer)?;
+
+        client.send_ixs_unconfirmed(&signer, &[signer], vec![ix], RpcLayer::Ephemeral)
+    }
+
+    /// Offline counterpart of [`World::write_state`]. With an active session
+    /// for `payer` the transaction is paid and already signed by the session
+    /// key, ready to submit.
+    pub fn build_write_state<T: MojoState>(
+        &self,
+        payer: &Pubkey,
+        name: &str,
+        new_state: &T,
+    ) -> Result<UnsignedTransaction, WorldError> {
+        let client = self.state_client(payer, name)?;
+        let (ix, session) = self.session_write_ix(payer, name, bytes_of(new_state));
+
+        match session {
+            Some(session) => {
+                let mut tx =
+                    client.build_unsigned(&session.pubkey(), vec![ix], RpcLayer::Ephemeral)?;
+                tx.partial_sign(session)?;
+                Ok(tx)
+            }
+            None => client.build_unsigned(payer, vec![ix], RpcLayer::Ephemeral),
+        }
+    }
+
+    /// Writes several states of `payer` on the ER, packing the writes into as
+    /// few transactions as fit. With `atomic` set, everything must fit in one
+    /// transaction or nothing is sent.
+    ///
+    /// Transactions are sent one after another and a failed one does not stop
+    /// the rest; each [`WriteBatch`] says which writes it carried and how it
+    /// went. The outer error means nothing was sent.
+    pub fn write_states(
+        &self,
+        payer: &impl Signer,
+        writes: &[StateWrite],
+        atomic: bool,
+    ) -> Result<Vec<WriteBatch>, WorldError> {
+        self.write_states_signed(&payer.pubkey(), Some(payer), writes, atomic)
+    }
+
+    /// [`World::write_states`] signed with `owner`'s session key.
+    pub fn write_states_as(
+        &self,
+        owner: &Pubkey,
+        writes: &[StateWrite],
+        atomic: bool,
+    ) -> Result<Vec<WriteBatch>, WorldError> {
+        self.write_states_signed(owner, None, writes, atomic)
+    }
+
+    fn write_states_signed(
+        &self,
+        owner: &Pubkey,
+        owner_signer: Option<&dyn Signer>,
+        writes: &[StateWrite],
+        atomic: bool,
+    ) -> Result<Vec<WriteBatch>, WorldError> {
+        let session = self.session(owner).map(|session| &session.keypair as _);
+        let signer = write_signer(owner, session, owner_signer)?;
+
+        // Writes to states delegated to different validators go to each
+        // one's ER, in the order they first show up.
+        let mut groups: Vec<(Arc<WorldClient>, Vec<usize>, Vec<Instruction>)> = Vec::new();
+        for (index, write) in writes.iter().enumerate() {
+            let (state_pda, _) = self.state_address(owner, write.name);
+            let client = self.ephemeral_client(&state_pda)?;
+            let (ix, _) = self.session_write_ix(owner, write.name, write.data);
+
+            match groups
+                .iter_mut()
+                .find(|(group, _, _)| group.validator() == client.validator())
+            {
+                Some((_, indices, ixs)) => {
+                    indices.push(index);
+                    ixs.push(ix);
+                }
+                None => groups.push((client, vec![index], vec![ix])),
+            }
+        }
+
+        if atomic {
+            if groups.len() > 1 {
+                return Err(WorldError::TransactionBuildError(
+                    "states are delegated to different validators".to_string(),
+                ));
+            }
+            if let Some((_, _, ixs)) = groups.first() {
+                if !fits_in_transaction(&signer.pubkey(), ixs) {
+                    return Err(WorldError::TransactionTooLarge {
+                        size: transaction_size(&signer.pubkey(), ixs),
+                        max: MAX_TRANSACTION_SIZE,
+                    });
+                }
+            }
+        }
+
+        // Pack everything up front so an oversized write fails before any
+        // transaction is sent.
+        let mut packed = Vec::new();
+        for (client, indices, ixs) in groups {
+            let mut indices = indices.into_iter();
+            for batch in pack_instructions(&signer.pubkey(), ixs)? {
+                let batch_indices = indices.by_ref().take(batch.len()).collect();
+                packed.push((Arc::clone(&client), batch_indices, batch));
+            }
+        }
+
+        Ok(packed
+            .into_iter()
+            .map(|(client, writes, batch)| WriteBatch {
+                writes,
+                result: client.send_ixs(&signer, batch, RpcLayer::Ephemeral),
+            })
+            .collect())
+    }
+
+    /// Overwrites byte ranges of a delegated state instead of sending the
+    /// whole `T`. Offsets are relative to the start of the state.
+    pub fn patch_state<T: MojoState>(
+        &self,
+        payer: &impl Signer,
+        name: &str,
+        patches: &[StatePatch],
+    ) -> Result<Signature, WorldError> {
+        self.patch_state_signed::<T>(&payer.pubkey(), Some(payer), name, patches)
+    }
+
+    /// [`World::patch_state`] signed with `owner`'s session key.
+    pub fn patch_state_as<T: MojoState>(
+        &self,
+        owner: &Pubkey,
+        name: &str,
+        patches: &[StatePatch],
+    ) -> Result<Signature, WorldError> {
+        self.patch_state_signed::<T>(owner, None, name, patches)
+    }
+
+    fn patch_state_signed<T: MojoState>(
+        &self,
+        owner: &Pubkey,
+        owner_signer: Option<&dyn Signer>,
+        name: &str,
+        patches: &[StatePatch],
+    ) -> Result<Signature, WorldError> {
+        // Reject patches the program would refuse before sending anything.
+        let mut simulated = vec![0u8; core::mem::size_of::<T>()];
+        apply_patches(&mut simulated, patches)?;
+
+        let client = self.state_client(owner, name)?;
+        let (ix, session) = self.session_patch_ix(owner, name, core::mem::size_of::<T>(), patches);
+        let signer = write_signer(owner, session.map(|key| key as _), owner_signer)?;
+
+        client.send_ixs(&signer, vec![ix], RpcLayer::Ephemeral)
+    }
+
+    /// Writes only what changed between `old_state` and `new_state`, falling
+    /// back to a full write when that is smaller. Returns `None` when the two
+    /// are identical and nothing was sent.
+    pub fn write_state_diff<T: MojoState>(
+        &self,
+        payer: &impl Signer,
+        name: &str,
+        old_state: &T,
+        new_state: &T,
+    ) -> Result<Option<Signature>, WorldError> {
+        self.write_state_diff_signed(&payer.pubkey(), Some(payer), name, old_state, new_state)
+    }
+
+    /// [`World::write_state_diff`] signed with `owner`'s session key.
+    pub fn write_state_diff_as<T: MojoState>(
+        &self,
+        owner: &Pubkey,
+        name: &str,
+        old_state: &T,
+        new_state: &T,
+    ) -> Result<Option<Signature>, WorldError> {
+        self.write_state_diff_signed(owner, None, name, old_state, new_state)
+    }
 
-        let rpc = match self.network {
-            RpcType::Devnet => RpcClient::new(ER_LAYER_RPC_DEVNET),
+    fn write_state_diff_signed<T: MojoState>(
+        &self,
+        owner: &Pubkey,
+        owner_signer: Option<&dyn Signer>,
+        name: &str,
+        old_state: &T,
+        new_state: &T,
+    ) -> Result<Option<Signature>, WorldError> {
+        let patches = diff_states(old_state, new_state);
+        if patches.is_empty() {
+            return Ok(None);
+        }
 
-            RpcType::Mainnet => RpcClient::new(ER_LAYER_RPC_MAINNET),
+        let patch_len = encode_patches(&patches).len();
+        let tx = if patch_len < core::mem::size_of::<T>() {
+            self.patch_state_signed::<T>(owner, owner_signer, name, &patches)?
+        } else {
+            self.write_state_signed(owner, owner_signer, name, new_state)?
         };
-        let data = rpc.get_account_data(&world_pda)?;
-        let required_len = core::mem::size_of::<T>();
-        ensure!(
-            data.len() >= required_len,
-            "account data length {} smaller than expected {}",
-            data.len(),
-            required_len
+        Ok(Some(tx))
+    }
+
+    pub async fn write_state_async<T: MojoState>(
+        &self,
+        payer: &(impl Signer + Sync),
+        name: &str,
+        new_state: &T,
+    ) -> Result<Signature, WorldError> {
+        self.write_state_async_signed(&payer.pubkey(), Some(payer), name, new_state)
+            .await
+    }
+
+    /// [`World::write_state_async`] signed with `owner`'s session key.
+    pub async fn write_state_async_as<T: MojoState>(
+        &self,
+        owner: &Pubkey,
+        name: &str,
+        new_state: &T,
+    ) -> Result<Signature, WorldError> {
+        self.write_state_async_signed(owner, None, name, new_state)
+            .await
+    }
+
+    async fn write_state_async_signed<T: MojoState>(
+        &self,
+        owner: &Pubkey,
+        owner_signer: Option<&(dyn Signer + Sync)>,
+        name: &str,
+        new_state: &T,
+    ) -> Result<Signature, WorldError> {
+        let (state_pda, _) = self.state_address(owner, name);
+        let client = self.async_ephemeral_client(&state_pda).await?;
+        let (ix, session) = self.session_write_ix(owner, name, bytes_of(new_state));
+        let signer = write_signer(owner, session.map(|key| key as _), owner_signer)?;
+
+        client
+            .send_ixs(&signer, vec![ix], RpcLayer::Ephemeral)
+            .await
+    }
+
+    /// Builds a write to `owner`'s state. When `owner` has an active session
+    /// the write is authorized by, and must be signed with, the returned
+    /// session key; the state PDA still derives from `owner`.
+    fn session_write_ix(
+        &self,
+        owner: &Pubkey,
+        name: &str,
+        data: &[u8],
+    ) -> (Instruction, Option<&Keypair>) {
+        let (state_pda, seed_hash) = self.state_address(owner, name);
+
+        match self.session(owner) {
+            Some(session) => {
+                let ix = write_with_session_ix(
+                    session.session_key(),
+                    *owner,
+                    self.world_address(),
+                    state_pda,
+                    seed_hash,
+                    data,
+                );
+                (ix, Some(&session.keypair))
+            }
+            None => (write_to_world_ix(*owner, state_pda, seed_hash, data), None),
+        }
+    }
+
+    /// [`World::session_write_ix`] for a patch of `owner`'s state.
+    fn session_patch_ix(
+        &self,
+        owner: &Pubkey,
+        name: &str,
+        state_size: usize,
+        patches: &[StatePatch],
+    ) -> (Instruction, Option<&Keypair>) {
+        let (state_pda, seed_hash) = self.state_address(owner, name);
+
+        match self.session(owner) {
+            Some(session) => {
+                let ix = patch_with_session_ix(
+                    session.session_key(),
+                    *owner,
+                    self.world_address(),
+                    state_pda,
+                    seed_hash,
+                    state_size,
+                    patches,
+                );
+                (ix, Some(&session.keypair))
+            }
+            None => (
+                patch_state_ix(*owner, state_pda, seed_hash, state_size, patches),
+                None,
+            ),
+        }
+    }
+
+    pub fn commit_state<T: MojoState>(
+        &self,
+        payer: &impl Signer,
+        name: &str,
+    ) -> Result<Signature, WorldError> {
+        let (state_pda, seed_hash) = self.state_address(&payer.pubkey(), name);
+        let ix = commit_state_ix(
+            payer.pubkey(),
+            state_pda,
+            seed_hash,
+            core::mem::size_of::<T>(),
         );
 
-        let state = *from_bytes::<T>(&data[..required_len]);
-        Ok(state)
+        self.ensure_delegated(&state_pda)?;
+        let tx =
+            self.ephemeral_client(&state_pda)?
+                .send_ixs(payer, vec![ix], RpcLayer::Ephemeral)?;
+        Ok(tx)
+    }
+
+    pub fn undelegate_state<T: MojoState>(
+        &self,
+        payer: &impl Signer,
+        name: &str,
+    ) -> Result<Signature, WorldError> {
+        let (state_pda, seed_hash) = self.state_address(&payer.pubkey(), name);
+        let ix = undelegate_account_ix(
+            payer.pubkey(),
+            state_pda,
+            seed_hash,
+            core::mem::size_of::<T>(),
+        );
+
+        self.ensure_delegated(&state_pda)?;
+        let tx =
+            self.ephemeral_client(&state_pda)?
+                .send_ixs(payer, vec![ix], RpcLayer::Ephemeral)?;
+        self.routes.lock().unwrap().validators.remove(&state_pda);
+        Ok(tx)
+    }
+
+    /// Fails with [`WorldError::NotDelegated`] unless the base-layer account
+    /// is owned by the delegation program.
+    fn ensure_delegated(&self, state: &Pubkey) -> Result<(), WorldError> {
+        let account = self
+            .client
+            .get_account(state, RpcLayer::BaseLayer)?
+            .ok_or_else(|| WorldError::AccountNotFound(state.to_string()))?;
+
+        if account.owner != DELEGATION_PROGRAM_ID {
+            return Err(WorldError::NotDelegated(*state));
+        }
+        Ok(())
+    }
+
+    /// Delegation record and metadata of a state account, or `None` when the
+    /// account is not delegated.
+    pub fn delegation_status(
+        &self,
+        owner: &Pubkey,
+        name: &str,
+    ) -> Result<Option<DelegationStatus>, WorldError> {
+        let (state_pda, _) = self.state_address(owner, name);
+        fetch_delegation_status(&self.client, &state_pda)
+    }
+
+    pub fn read_state<T: MojoState>(&self, owner: &Pubkey, name: &str) -> Result<T, WorldError> {
+        let (state_pda, _) = self.state_address(owner, name);
+        self.read_account_from(&state_pda, ReadSource::Ephemeral)
+    }
+
+    /// Like [`World::read_state`], but lets the caller pick the layer. `Auto`
+    /// reads the base-layer account and only goes to the ER when that account
+    /// is owned by the delegation program.
+    pub fn read_state_from<T: MojoState>(
+        &self,
+        owner: &Pubkey,
+        name: &str,
+        source: ReadSource,
+    ) -> Result<T, WorldError> {
+        let (state_pda, _) = self.state_address(owner, name);
+        self.read_account_from(&state_pda, source)
+    }
+
+    /// Reads the base-layer and ER copies of a state side by side, e.g. to
+    /// check what a pending commit will change. Missing accounts come back as `None`.
+    pub fn read_state_both<T: MojoState>(
+        &self,
+        owner: &Pubkey,
+        name: &str,
+    ) -> Result<StateCopies<T>, WorldError> {
+        let (state_pda, _) = self.state_address(owner, name);
+
+        let read = |client: &WorldClient, layer| -> Result<Option<T>, WorldError> {
+            client
+                .get_account(&state_pda, layer)?
+                .map(|account| decode_state(&account.data))
+                .transpose()
+        };
+
+        Ok(StateCopies {
+            base: read(&self.client, RpcLayer::BaseLayer)?,
+            ephemeral: read(&*self.ephemeral_client(&state_pda)?, RpcLayer::Ephemeral)?,
+        })
+    }
+
+    /// Reads many states from the ER in as few requests as possible. Each
+    /// entry of the result matches the key at the same index; accounts that do
+    /// not exist come back as `Ok(None)` rather than failing the batch.
+    pub fn read_states<T: MojoState>(
+        &self,
+        keys: &[(Pubkey, &str)],
+    ) -> Result<Vec<Result<Option<T>, WorldError>>, WorldError> {
+        let addresses: Vec<Pubkey> = keys
+            .iter()
+            .map(|(owner, name)| self.state_address(owner, name).0)
+            .collect();
+
+        // States delegated to different validators are read from each one's ER.
+        let mut groups: HashMap<Pubkey, (Arc<WorldClient>, Vec<usize>)> = HashMap::new();
+        for (index, validator) in self.state_validators(&addresses)?.into_iter().enumerate() {
+            groups
+                .entry(validator.identity)
+                .or_insert_with(|| (self.clients_for(&validator).0, Vec::new()))
+                .1
+                .push(index);
+        }
+
+        let mut results: Vec<Result<Option<T>, WorldError>> =
+            keys.iter().map(|_| Ok(None)).collect();
+        for (client, indices) in groups.into_values() {
+            let group: Vec<Pubkey> = indices.iter().map(|index| addresses[*index]).collect();
+            let accounts = client.get_multiple_accounts(&group, RpcLayer::Ephemeral)?;
+
+            for (index, account) in indices.into_iter().zip(accounts) {
+                results[index] = account
+                    .map(|account| decode_state(&account.data))
+                    .transpose();
+            }
+        }
+        Ok(results)
+    }
+
+    pub async fn read_state_async<T: MojoState>(
+        &self,
+        owner: &Pubkey,
+        name: &str,
+    ) -> Result<T, WorldError> {
+        let (state_pda, _) = self.state_address(owner, name);
+        let account = self
+            .async_ephemeral_client(&state_pda)
+            .await?
+            .get_account(&state_pda, RpcLayer::Ephemeral)
+            .await?
+            .ok_or_else(|| WorldError::AccountNotFound(state_pda.to_string()))?;
+        decode_state(&account.data)
+    }
+
+    /// Streams updates of a state account on the ER layer, reconnecting and
+    /// resubscribing when the websocket drops. A consumer that falls behind
+    /// skips to the newest state rather than buffering every update. Must be
+    /// called from within a tokio runtime; the subscription ends when the
+    /// stream is dropped.
+    pub fn subscribe_state<T: MojoState + Send>(
+        &self,
+        owner: &Pubkey,
+        name: &str,
+    ) -> BoxStream<'static, Result<T, WorldError>> {
+        let (state_pda, _) = self.state_address(owner, name);
+        let (updates, receiver) = mpsc::channel(UPDATE_BUFFER);
+
+        // The delegation record is looked up inside the task, which keeps
+        // this call free of blocking RPCs.
+        let cached = self
+            .routes
+            .lock()
+            .unwrap()
+            .validators
+            .get(&state_pda)
+            .cloned();
+        let client = Arc::clone(&self.async_client);
+        let network = self.network.clone();
+        let candidates = self.validator_candidates();
+        tokio::spawn(async move {
+            let validator = match cached {
+                Some(validator) => Ok(validator),
+                None => fetch_delegation_status_async(&client, &state_pda)
+                    .await
+                    .and_then(|status| match status {
+                        Some(status) => find_validator(&candidates, &status.authority),
+                        None => Ok(candidates[0].clone()),
+                    }),
+            };
+
+            match validator {
+                Ok(validator) => {
+                    let ws_url = network.ws_url(RpcLayer::Ephemeral, &validator);
+                    watch_account(ws_url, state_pda, client.config.commitment, updates).await
+                }
+                Err(err) => {
+                    let _ = updates.send(Err(err)).await;
+                }
+            }
+        });
+
+        stream::unfold(receiver, |mut receiver| async move {
+            receiver.recv().await.map(|update| (update, receiver))
+        })
+        .boxed()
+    }
+
+    /// Address and seed hash of a state account, namespaced under this world.
+    pub fn state_address(&self, owner: &Pubkey, name: &str) -> (Pubkey, [u8; 32]) {
+        let world = self.world_address();
+        let (state_pda, _) = find_state_pda(&world, name, owner);
+        (state_pda, state_seed_hash(&world, name, owner))
+    }
+
+    pub fn world_address(&self) -> Pubkey {
+        Pubkey::new_from_array(self.data.world_address)
+    }
+
+    /// Looks up a state account created before states were namespaced under
+    /// their world. Returns `None` when no legacy account exists on the base layer.
+    pub fn find_legacy_state(
+        &self,
+        owner: &Pubkey,
+        name: &str,
+    ) -> Result<Option<Pubkey>, WorldError> {
+        let (legacy_pda, _) = find_legacy_state_pda(owner, name);
+
+        let account = self.client.get_account(&legacy_pda, RpcLayer::BaseLayer)?;
+        Ok(account.map(|_| legacy_pda))
+    }
+
+    /// Reads a state account stored under the legacy derivation so it can be
+    /// copied into its namespaced address with `create_state`. Like
+    /// [`World::find_legacy_state`], it starts from the base layer and only
+    /// reads the ER copy when the account is delegated.
+    pub fn read_legacy_state<T: MojoState>(
+        &self,
+        owner: &Pubkey,
+        name: &str,
+    ) -> Result<T, WorldError> {
+        let (legacy_pda, _) = find_legacy_state_pda(owner, name);
+        self.read_account_from(&legacy_pda, ReadSource::Auto)
+    }
+
+    fn read_account_from<T: MojoState>(
+        &self,
+        address: &Pubkey,
+        source: ReadSource,
+    ) -> Result<T, WorldError> {
+        let layer = match source {
+            ReadSource::BaseLayer => RpcLayer::BaseLayer,
+            ReadSource::Ephemeral => RpcLayer::Ephemeral,
+            ReadSource::Auto => {
+                let account = self
+                    .client
+                    .get_account(address, RpcLayer::BaseLayer)?
+                    .ok_or_else(|| WorldError::AccountNotFound(address.to_string()))?;
+                if account.owner != DELEGATION_PROGRAM_ID {
+                    return decode_state(&account.data);
+                }
+                RpcLayer::Ephemeral
+            }
+        };
+
+        let account = match layer {
+            RpcLayer::BaseLayer => self.client.get_account(address, layer)?,
+            RpcLayer::Ephemeral => self
+                .ephemeral_client(address)?
+                .get_account(address, layer)?,
+        };
+        let account = account.ok_or_else(|| WorldError::AccountNotFound(address.to_string()))?;
+        decode_state(&account.data)
+    }
+
+    /// This world's validator first, then the registered and built-in ones.
+    fn validator_candidates(&self) -> Vec<Validator> {
+        std::iter::once(self.validator.clone())
+            .chain(self.known_validators.iter().cloned())
+            .collect()
+    }
+
+    /// Validator whose ER holds `state`: the one its delegation record names,
+    /// or this world's validator while the state is not delegated.
+    fn state_validator(&self, state: &Pubkey) -> Result<Validator, WorldError> {
+        if let Some(validator) = self.routes.lock().unwrap().validators.get(state) {
+            return Ok(validator.clone());
+        }
+
+        let status = fetch_delegation_status(&self.client, state)?;
+        self.route_from_status(state, status.as_ref())
+    }
+
+    /// [`World::state_validator`] for many states, looking up the ones not
+    /// routed yet with a single batched base-layer read.
+    fn state_validators(&self, states: &[Pubkey]) -> Result<Vec<Validator>, WorldError> {
+        let cached: Vec<Option<Validator>> = {
+            let routes = self.routes.lock().unwrap();
+            states
+                .iter()
+                .map(|state| routes.validators.get(state).cloned())
+                .collect()
+        };
+
+        let missing: Vec<Pubkey> = states
+            .iter()
+            .zip(&cached)
+            .filter(|(_, validator)| validator.is_none())
+            .map(|(state, _)| *state)
+            .collect();
+        let mut statuses = fetch_delegation_statuses(&self.client, &missing)?.into_iter();
+
+        states
+            .iter()
+            .zip(cached)
+            .map(|(state, validator)| match validator {
+                Some(validator) => Ok(validator),
+                None => {
+                    let status = statuses.next().expect("one status per missing state");
+                    self.route_from_status(state, status.as_ref())
+                }
+            })
+            .collect()
+    }
+
+    async fn state_validator_async(&self, state: &Pubkey) -> Result<Validator, WorldError> {
+        if let Some(validator) = self.routes.lock().unwrap().validators.get(state) {
+            return Ok(validator.clone());
+        }
+
+        let status = fetch_delegation_status_async(&self.async_client, state).await?;
+        self.route_from_status(state, status.as_ref())
+    }
+
+    /// Caches and returns the validator `status` delegates `state` to. A
+    /// state that is not delegated is routed to this world's validator, the
+    /// one it would be delegated to.
+    fn route_from_status(
+        &self,
+        state: &Pubkey,
+        status: Option<&DelegationStatus>,
+    ) -> Result<Validator, WorldError> {
+        let validator = match status {
+            Some(status) => find_validator(&self.validator_candidates(), &status.authority)?,
+            None => self.validator.clone(),
+        };
+        self.remember_route(*state, validator.clone());
+        Ok(validator)
+    }
+
+    fn remember_route(&self, state: Pubkey, validator: Validator) {
+        self.routes
+            .lock()
+            .unwrap()
+            .validators
+            .insert(state, validator);
+    }
+
+    /// Clients whose ER endpoint is the one of `validator`.
+    fn clients_for(&self, validator: &Validator) -> (Arc<WorldClient>, Arc<AsyncWorldClient>) {
+        if *validator == self.validator {
+            return (Arc::clone(&self.client), Arc::clone(&self.async_client));
+        }
+
+        let config = &self.client.config;
+        self.routes
+            .lock()
+            .unwrap()
+            .clients
+            .entry(validator.identity)
+            .or_insert_with(|| {
+                (
+                    Arc::new(WorldClient::with_config(
+                        &self.network,
+                        validator.clone(),
+                        config.clone(),
+                    )),
+                    Arc::new(AsyncWorldClient::with_config(
+                        &self.network,
+                        validator.clone(),
+                        config.clone(),
+                    )),
+                )
+            })
+            .clone()
+    }
+
+    fn ephemeral_client(&self, state: &Pubkey) -> Result<Arc<WorldClient>, WorldError> {
+        let validator = self.state_validator(state)?;
+        Ok(self.clients_for(&validator).0)
+    }
+
+    fn state_client(&self, owner: &Pubkey, name: &str) -> Result<Arc<WorldClient>, WorldError> {
+        let (state_pda, _) = self.state_address(owner, name);
+        self.ephemeral_client(&state_pda)
+    }
+
+    async fn async_ephemeral_client(
+        &self,
+        state: &Pubkey,
+    ) -> Result<Arc<AsyncWorldClient>, WorldError> {
+        let validator = self.state_validator_async(state).await?;
+        Ok(self.clients_for(&validator).1)
     }
 
     pub async fn create_profile_picture(
         &self,
-        user: &impl Signer,
-        payer: Option<&impl Signer>,
+        user: &(impl Signer + Sync),
+        payer: Option<&(impl Signer + Sync)>,
         image_source: ImageSource,
         name: &str,
         description: Option<&str>,
         uploader: Option<ArweaveUploader>,
-    ) -> Result<ProfilePicture> {
-        let image_data = load_image_data(&image_source).await?;
-        validate_image(&image_data)?;
-
-        let uploader = uploader.unwrap_or_default();
-        let image_tx_id = uploader.upload(&image_data, Some("image/png")).await?;
-        let image_uri = uploader.uri_from_tx_id(&image_tx_id);
-
-        let metadata = crate::profile::Metadata::new(name, description, &image_uri);
-
-        let metadata_json = serde_json::to_vec(&metadata)
-            .map_err(|e| anyhow::anyhow!("Failed to serialize metadata: {}", e))?;
-        let metadata_tx_id = uploader
-            .upload(&metadata_json, Some("application/json"))
-            .await?;
-        let metadata_uri = uploader.uri_from_tx_id(&metadata_tx_id);
+    ) -> Result<ProfilePicture, WorldError> {
+        let metadata_uri =
+            upload_profile_metadata(image_source, name, description, uploader).await?;
 
         let asset_keypair = Keypair::new();
         let asset_pubkey = asset_keypair.pubkey();
@@ -164,22 +1427,16 @@ impl World {
             &metadata_uri,
         )?;
 
-        let signers: Vec<&dyn Signer> = vec![user as &dyn Signer];
+        let signers: Vec<&(dyn Signer + Sync)> = vec![user, &asset_keypair];
 
         if let Some(p) = payer {
-            WorldClient::new(&self.network).send_ixs_with_payer(
-                p,
-                &signers,
-                vec![create_ix],
-                RpcLayer::BaseLayer,
-            )?;
+            self.async_client
+                .send_ixs_with_payer(p, &signers, vec![create_ix], RpcLayer::BaseLayer)
+                .await?;
         } else {
-            WorldClient::new(&self.network).send_ixs_with_payer(
-                user,
-                &signers,
-                vec![create_ix],
-                RpcLayer::BaseLayer,
-            )?;
+            self.async_client
+                .send_ixs_with_payer(user, &signers, vec![create_ix], RpcLayer::BaseLayer)
+                .await?;
         }
 
         Ok(ProfilePicture {
@@ -189,13 +1446,52 @@ impl World {
         })
     }
 
-    pub async fn get_profile_picture(&self, asset: &Pubkey) -> Result<ProfilePictureData> {
-        let rpc = match self.network {
-            RpcType::Devnet => RpcClient::new(BASE_LAYER_RPC_DEVNET),
-            RpcType::Mainnet => RpcClient::new(BASE_LAYER_RPC_MAINNET),
+    /// Offline counterpart of [`World::create_profile_picture`]: uploads the
+    /// image and metadata, then returns the mint transaction already signed by
+    /// the new asset key and waiting on `user` (and `payer`, if given).
+    pub async fn build_create_profile_picture(
+        &self,
+        user: &Pubkey,
+        payer: Option<&Pubkey>,
+        image_source: ImageSource,
+        name: &str,
+        description: Option<&str>,
+        uploader: Option<ArweaveUploader>,
+    ) -> Result<(ProfilePicture, UnsignedTransaction), WorldError> {
+        let metadata_uri =
+            upload_profile_metadata(image_source, name, description, uploader).await?;
+
+        let asset_keypair = Keypair::new();
+        let effective_payer = payer.copied().unwrap_or(*user);
+        let create_ix = create_mpl_core_asset_ix(
+            &asset_keypair.pubkey(),
+            *user,
+            effective_payer,
+            name,
+            &metadata_uri,
+        )?;
+
+        let mut tx = self
+            .async_client
+            .build_unsigned(&effective_payer, vec![create_ix], RpcLayer::BaseLayer)
+            .await?;
+        tx.partial_sign(&asset_keypair)?;
+
+        let profile_picture = ProfilePicture {
+            asset: asset_keypair.pubkey(),
+            collection: None,
+            owner: *user,
         };
+        Ok((profile_picture, tx))
+    }
+
+    pub async fn get_profile_picture(
+        &self,
+        asset: &Pubkey,
+    ) -> Result<ProfilePictureData, WorldError> {
+        let rpc = self.async_client.rpc(RpcLayer::BaseLayer);
 
-        let mpl_asset = fetch_mpl_core_asset(&rpc, asset)?;
+        let mpl_asset = fetch_mpl_core_asset_async(rpc, asset).await?;
 
         let owner = mpl_asset.base.owner;
         let collection = None;
@@ -214,3 +1510,178 @@ impl World {
         })
     }
 }
+
+async fn upload_profile_metadata(
+    image_source: ImageSource,
+    name: &str,
+    description: Option<&str>,
+    uploader: Option<ArweaveUploader>,
+) -> Result<String, WorldError> {
+    let image_data = load_image_data(&image_source).await?;
+    validate_image(&image_data)?;
+
+    let uploader = uploader.unwrap_or_default();
+    let image_tx_id = uploader
+        .upload(&image_data, Some("image/png"))
+        .await
+        .map_err(|e| WorldError::ImageUploadError(upload_error_message(e)))?;
+    let image_uri = uploader.uri_from_tx_id(&image_tx_id);
+
+    let metadata = crate::profile::Metadata::new(name, description, &image_uri);
+
+    let metadata_json =
+        serde_json::to_vec(&metadata).map_err(|_| WorldError::SerializationError)?;
+    let metadata_tx_id = uploader
+        .upload(&metadata_json, Some("application/json"))
+        .await
+        .map_err(|e| WorldError::MetadataUploadError(upload_error_message(e)))?;
+    Ok(uploader.uri_from_tx_id(&metadata_tx_id))
+}
+
+/// The key that signs an ER write to `owner`'s states: their session key when
+/// they have one, `owner_signer` otherwise.
+fn write_signer<'a, S: Signer + ?Sized>(
+    owner: &Pubkey,
+    session: Option<&'a S>,
+    owner_signer: Option<&'a S>,
+) -> Result<&'a S, WorldError> {
+    session
+        .or(owner_signer)
+        .ok_or_else(|| WorldError::SessionError(format!("no active session for {owner}")))
+}
+
+/// Create and delegate as one transaction, with the compute budget both need.
+fn create_and_delegate_ixs(create_ix: Instruction, delegate_ix: Instruction) -> Vec<Instruction> {
+    vec![
+        ComputeBudgetInstruction::set_compute_unit_limit(CREATE_AND_DELEGATE_COMPUTE_UNITS),
+        create_ix,
+        delegate_ix,
+    ]
+}
+
+/// The uploader's own message, without the "Upload failed" prefix the
+/// image and metadata variants would repeat.
+fn upload_error_message(error: WorldError) -> String {
+    match error {
+        WorldError::UploadError(message) => message,
+        other => other.to_string(),
+    }
+}
+
+/// The validator with `identity` among `candidates` or the built-in ones.
+fn find_validator(candidates: &[Validator], identity: &Pubkey) -> Result<Validator, WorldError> {
+    candidates
+        .iter()
+        .find(|validator| validator.identity == *identity)
+        .cloned()
+        .or_else(|| Validator::from_identity(identity))
+        .ok_or(WorldError::UnknownValidator(*identity))
+}
+
+pub(crate) fn decode_state<T: MojoState>(data: &[u8]) -> Result<T, WorldError> {
+    let required_len = core::mem::size_of::<T>();
+    if data.len() < required_len {
+        return Err(WorldError::AccountSizeMismatch {
+            expected: required_len,
+            actual: data.len(),
+        });
+    }
+
+    Ok(pod_read_unaligned::<T>(&data[..required_len]))
+}
+
+fn verify_world_data(address: &Pubkey, data: &WorldData) -> Result<(), WorldError> {
+    let stored = Pubkey::new_from_array(data.world_address);
+    if stored != *address {
+        return Err(WorldError::PdaMismatch {
+            expected: *address,
+            found: stored,
+        });
+    }
+
+    let (derived, _) = Pubkey::find_program_address(&[&data.seed, &data.creator], &PROGRAM_ID);
+    if derived != *address {
+        return Err(WorldError::PdaMismatch {
+            expected: *address,
+            found: derived,
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test_world {
+    use super::*;
+
+    fn world_data(owner: &Pubkey, name: &str) -> WorldData {
+        let (world_pda, _) = find_world_pda(owner, name);
+        WorldData {
+            creator: owner.to_bytes(),
+            seed: world_seed_hash(owner, name),
+            world_address: world_pda.to_bytes(),
+        }
+    }
+
+    #[test]
+    fn verifies_derived_world() {
+        let owner = Pubkey::new_unique();
+        let (world_pda, _) = find_world_pda(&owner, "arena");
+
+        assert!(verify_world_data(&world_pda, &world_data(&owner, "arena")).is_ok());
+    }
+
+    #[test]
+    fn rejects_world_with_foreign_seed() {
+        let owner = Pubkey::new_unique();
+        let (world_pda, _) = find_world_pda(&owner, "arena");
+        let mut data = world_data(&owner, "arena");
+        data.seed = world_seed_hash(&owner, "lobby");
+
+        assert!(matches!(
+            verify_world_data(&world_pda, &data),
+            Err(WorldError::PdaMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn session_key_signs_writes_when_present() {
+        let owner = Keypair::new();
+        let session = Keypair::new();
+        let owner_signer: Option<&dyn Signer> = Some(&owner);
+
+        let signer = write_signer(&owner.pubkey(), Some(&session as _), owner_signer).unwrap();
+        assert_eq!(signer.pubkey(), session.pubkey());
+
+        let signer = write_signer(&owner.pubkey(), None, owner_signer).unwrap();
+        assert_eq!(signer.pubkey(), owner.pubkey());
+
+        assert!(matches!(
+            write_signer::<dyn Signer>(&owner.pubkey(), None, None),
+            Err(WorldError::SessionError(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_session_of_another_world() {
+        let owner = Pubkey::new_unique();
+        let world = World::from_client(
+            world_data(&owner, "arena"),
+            WorldClient::new(&RpcType::Localnet),
+        );
+        let (lobby, _) = find_world_pda(&owner, "lobby");
+
+        let foreign = Session::generate(owner, lobby, Duration::from_secs(600));
+        assert!(matches!(
+            world.check_session(&foreign, &owner),
+            Err(WorldError::SessionError(_))
+        ));
+
+        let session = Session::generate(owner, world.world_address(), Duration::from_secs(600));
+        assert!(matches!(
+            world.check_session(&session, &Pubkey::new_unique()),
+            Err(WorldError::SessionError(_))
+        ));
+        assert!(world.with_session(session).is_ok());
+    }
+}